@@ -0,0 +1,71 @@
+//! Errors that can occur when running tests or parsing their output.
+
+use std::{io, process::ExitStatus};
+
+/// An error running tests, or parsing their output.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The `cargo` command couldn't be found.
+    #[error("couldn't find `cargo`: is Rust installed, and on your PATH?")]
+    CargoNotFound,
+    /// The test command couldn't be started.
+    #[error("couldn't start {command}")]
+    Spawn {
+        command: String,
+        #[source]
+        source: io::Error,
+    },
+    /// The tests couldn't be built. `output` contains cargo's output up to
+    /// that point, including any compiler diagnostics.
+    #[error("build failed ({status})")]
+    BuildFailed {
+        status: ExitStatus,
+        output: Vec<String>,
+    },
+    /// A line of output looked like a test event, but couldn't be parsed.
+    #[error("couldn't parse test output {0:?}")]
+    Unparseable(String),
+    /// The test command failed before any tests started running, but not
+    /// because the tests couldn't be built: for example, because cargo
+    /// didn't accept one of its arguments. `output` contains its output.
+    #[error("test command failed ({status})")]
+    CommandFailed {
+        status: ExitStatus,
+        output: Vec<String>,
+    },
+    /// A source file couldn't be parsed as Rust (see [`scan`](crate::scan)).
+    #[cfg(feature = "scan")]
+    #[error("couldn't parse {}", file.display())]
+    Syntax {
+        file: std::path::PathBuf,
+        #[source]
+        source: syn::Error,
+    },
+    /// The test results parsed for a test binary don't match the counts
+    /// libtest reported for it, so some results must have been missed.
+    #[error("{target}: libtest reported {reported}, but testdox found {parsed}")]
+    CountMismatch {
+        target: String,
+        reported: String,
+        parsed: String,
+    },
+    /// A test result had a status that testdox doesn't understand.
+    #[error("unhandled test status {0:?}")]
+    UnknownStatus(String),
+    /// No such test runner is supported.
+    #[error("unknown test runner {0:?}")]
+    UnknownRunner(String),
+    /// The chosen test runner can't do what was asked of it.
+    #[error("{0} is only supported with the cargo runner")]
+    UnsupportedRunner(&'static str),
+    /// No such output format is supported.
+    #[error("unknown output format {0:?}")]
+    UnknownFormat(String),
+    /// Reading the output of the test command, or waiting for it, failed.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A `Result` whose error type is [`Error`].
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,460 @@
+//! Presenting test results in various formats.
+
+use colored::Colorize;
+use std::{collections::BTreeMap, io::Write, str::FromStr};
+
+use crate::{symbols, Error, Summary, Target, TestResult};
+
+mod html;
+#[cfg(feature = "json")]
+mod json;
+mod junit;
+mod markdown;
+mod tap;
+
+use html::Html;
+#[cfg(feature = "json")]
+pub use json::read_json;
+#[cfg(feature = "json")]
+use json::{Json, JsonLines};
+use junit::Junit;
+use markdown::Markdown;
+use tap::Tap;
+
+/// How test results should be presented.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub enum Format {
+    /// One line per test, printed as soon as each test finishes, under a
+    /// heading for each test binary.
+    #[default]
+    Flat,
+    /// Tests grouped under a heading for each test binary and module, with
+    /// nested modules shown as a tree.
+    Grouped,
+    /// A Markdown specification document, with a checklist of tests for each
+    /// module.
+    Markdown,
+    /// A self-contained HTML report, with collapsible modules and details of
+    /// any failures.
+    Html,
+    /// A JUnit XML report, for CI systems.
+    Junit,
+    /// A Test Anything Protocol (TAP) stream, with a test point written as
+    /// soon as each test finishes.
+    Tap,
+    /// A JSON document containing every result, and a summary.
+    #[cfg(feature = "json")]
+    Json,
+    /// A JSON object for each result, one per line, printed as soon as each
+    /// test finishes, followed by a summary.
+    #[cfg(feature = "json")]
+    JsonLines,
+}
+
+impl Format {
+    /// Creates a [`Reporter`] that writes results in this format to `out`.
+    ///
+    /// The output of failed tests is shown beneath them, limited to
+    /// `failure_lines` lines if given. If `ascii` is true, only ASCII
+    /// characters are used, with `PASS`, `FAIL`, and `SKIP` markers instead
+    /// of `✔`, `x`, and `?`.
+    pub fn reporter<'a>(
+        self,
+        out: impl Write + 'a,
+        failure_lines: Option<usize>,
+        ascii: bool,
+    ) -> Box<dyn Reporter + 'a> {
+        match self {
+            Format::Flat => Box::new(Flat {
+                out,
+                failure_lines,
+                ascii,
+                target: None,
+            }),
+            Format::Grouped => Box::new(Grouped {
+                out,
+                failure_lines,
+                ascii,
+                targets: Targets::default(),
+            }),
+            Format::Markdown => Box::new(Markdown {
+                out,
+                targets: Targets::default(),
+            }),
+            Format::Html => Box::new(Html {
+                out,
+                targets: Targets::default(),
+            }),
+            Format::Junit => Box::new(Junit {
+                out,
+                targets: Targets::default(),
+            }),
+            Format::Tap => Box::new(Tap {
+                out,
+                ascii,
+                count: 0,
+                target: None,
+            }),
+            #[cfg(feature = "json")]
+            Format::Json => Box::new(Json {
+                out,
+                results: Vec::new(),
+            }),
+            #[cfg(feature = "json")]
+            Format::JsonLines => Box::new(JsonLines { out }),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "flat" => Ok(Format::Flat),
+            "grouped" => Ok(Format::Grouped),
+            "markdown" => Ok(Format::Markdown),
+            "html" => Ok(Format::Html),
+            "junit" => Ok(Format::Junit),
+            "tap" => Ok(Format::Tap),
+            #[cfg(feature = "json")]
+            "json" => Ok(Format::Json),
+            #[cfg(feature = "json")]
+            "json-lines" => Ok(Format::JsonLines),
+            _ => Err(Error::UnknownFormat(format.to_string())),
+        }
+    }
+}
+
+/// Presents test results as they arrive.
+pub trait Reporter {
+    /// Reports the result of a single test.
+    ///
+    /// # Errors
+    ///
+    /// If writing the output fails.
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()>;
+
+    /// Finishes the report, once all results have been seen, with a summary
+    /// of the whole run.
+    ///
+    /// # Errors
+    ///
+    /// If writing the output fails.
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()>;
+}
+
+/// Formats the heading shown for a test binary.
+fn heading(target: &Target) -> String {
+    format!("{} ({})", target.display_name().bold(), target.kind)
+}
+
+/// Writes the output of a failed test, indented by `indent`, and limited to
+/// `max_lines` lines if given.
+fn write_failure(
+    out: &mut impl Write,
+    failure: &str,
+    indent: &str,
+    max_lines: Option<usize>,
+    ascii: bool,
+) -> std::io::Result<()> {
+    let lines: Vec<_> = failure.lines().collect();
+    let shown = max_lines.unwrap_or(lines.len()).min(lines.len());
+    for line in &lines[..shown] {
+        writeln!(out, "{indent}{line}")?;
+    }
+    if shown < lines.len() {
+        let hidden = lines.len() - shown;
+        let noun = if hidden == 1 { "line" } else { "lines" };
+        let more = format!("{} ({hidden} more {noun})", symbols::ellipsis(ascii));
+        writeln!(out, "{indent}{}", more.dimmed())?;
+    }
+    Ok(())
+}
+
+/// Escapes the characters that have special meaning in HTML or XML text, and
+/// removes any control characters (such as ANSI escape codes) that aren't
+/// allowed there.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            _ if c.is_control() => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct Flat<W> {
+    out: W,
+    failure_lines: Option<usize>,
+    ascii: bool,
+    /// The target whose heading was printed most recently.
+    target: Option<Target>,
+}
+
+impl<W: Write> Reporter for Flat<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        if result.target.is_some() && result.target != self.target {
+            self.target.clone_from(&result.target);
+            if let Some(target) = &self.target {
+                writeln!(self.out, "{}", heading(target))?;
+            }
+        }
+        if self.ascii {
+            writeln!(self.out, "{result:#}")?;
+        } else {
+            writeln!(self.out, "{result}")?;
+        }
+        if let Some(failure) = &result.failure {
+            write_failure(
+                &mut self.out,
+                failure,
+                "    ",
+                self.failure_lines,
+                self.ascii,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        writeln!(self.out, "\n{summary}")?;
+        self.out.flush()
+    }
+}
+
+struct Grouped<W> {
+    out: W,
+    failure_lines: Option<usize>,
+    ascii: bool,
+    targets: Targets,
+}
+
+/// Results collected for formats that present them all at the end, grouped
+/// by test binary (in the order they ran) and then by module.
+#[derive(Default)]
+struct Targets(Vec<(Option<Target>, Module)>);
+
+impl Targets {
+    fn insert(&mut self, result: &TestResult) {
+        let index = self
+            .0
+            .iter()
+            .position(|(target, _)| *target == result.target)
+            .unwrap_or_else(|| {
+                self.0.push((result.target.clone(), Module::default()));
+                self.0.len() - 1
+            });
+        self.0[index].1.insert(result.clone());
+    }
+}
+
+/// A node in the module tree: the tests it contains directly (sorted by
+/// name), and its submodules, by name.
+#[derive(Default)]
+struct Module {
+    tests: Vec<TestResult>,
+    children: BTreeMap<String, Module>,
+}
+
+impl Module {
+    fn insert(&mut self, result: TestResult) {
+        let mut module = self;
+        if let Some(path) = &result.module {
+            for part in path.split("::") {
+                module = module.children.entry(part.to_string()).or_default();
+            }
+        }
+        let index = module
+            .tests
+            .partition_point(|test| test.name <= result.name);
+        module.tests.insert(index, result);
+    }
+
+    /// Returns the full path and tests of this module and each of its
+    /// descendants that contain tests, in depth-first order.
+    fn flatten(&self) -> Vec<(Option<String>, &[TestResult])> {
+        let mut modules = Vec::new();
+        self.flatten_into(None, &mut modules);
+        modules
+    }
+
+    fn flatten_into<'a>(
+        &'a self,
+        path: Option<&str>,
+        modules: &mut Vec<(Option<String>, &'a [TestResult])>,
+    ) {
+        if !self.tests.is_empty() {
+            modules.push((path.map(String::from), &self.tests));
+        }
+        for (name, child) in &self.children {
+            let path = match path {
+                Some(path) => format!("{path}::{name}"),
+                None => name.clone(),
+            };
+            child.flatten_into(Some(&path), modules);
+        }
+    }
+
+    fn write(
+        &self,
+        out: &mut impl Write,
+        depth: usize,
+        failure_lines: Option<usize>,
+        ascii: bool,
+    ) -> std::io::Result<()> {
+        let indent = "  ".repeat(depth);
+        for test in &self.tests {
+            let (name, suffixes) = (&test.name, test.suffixes());
+            if ascii {
+                writeln!(out, "{indent}{:#} {name}{suffixes}", test.status)?;
+            } else {
+                writeln!(out, "{indent}{} {name}{suffixes}", test.status)?;
+            }
+            if let Some(failure) = &test.failure {
+                let indent = format!("{indent}    ");
+                write_failure(out, failure, &indent, failure_lines, ascii)?;
+            }
+        }
+        for (name, child) in &self.children {
+            writeln!(out, "{indent}{}", name.bright_blue())?;
+            child.write(out, depth + 1, failure_lines, ascii)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Reporter for Grouped<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.targets.insert(result);
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        for (target, module) in &self.targets.0 {
+            match target {
+                Some(target) => {
+                    writeln!(self.out, "{}", heading(target))?;
+                    module.write(&mut self.out, 1, self.failure_lines, self.ascii)?;
+                }
+                None => module.write(&mut self.out, 0, self.failure_lines, self.ascii)?,
+            }
+        }
+        writeln!(self.out, "\n{summary}")?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_test_results;
+
+    /// Writes the given results in the given format, followed by a summary
+    /// of them, returning the output.
+    pub(super) fn render(format: Format, results: &[TestResult]) -> String {
+        let mut out = Vec::new();
+        let mut reporter = format.reporter(&mut out, None, false);
+        let mut summary = Summary::default();
+        for result in results {
+            reporter.result(result).unwrap();
+            summary.add(result);
+        }
+        reporter.finish(&summary).unwrap();
+        drop(reporter);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn flat_format_shows_a_heading_for_each_test_binary() {
+        colored::control::set_override(false);
+        let results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test foo::tests::does_foo_stuff ... ok
+test it_works ... ok
+     Running tests/cli.rs (target/debug/deps/cli-0123456789abcdef)
+test it_works ... FAILED
+",
+        );
+        assert_eq!(
+            render(Format::Flat, &results),
+            "demo (lib)
+✔ foo – does foo stuff
+✔ it works
+cli (test)
+x it works
+
+2 passed, 1 failed, 0 ignored in 0.00s
+"
+        );
+    }
+
+    #[test]
+    fn grouped_format_shows_modules_as_a_tree() {
+        colored::control::set_override(false);
+        let results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test foo::tests::does_foo_stuff ... ok
+test it_works ... ok
+test foo::bar::tests::does_bar_stuff ... FAILED
+test baz::test::is_ignored ... ignored, not yet
+test foo::also_does_foo_stuff ... ok
+     Running tests/cli.rs (target/debug/deps/cli-0123456789abcdef)
+test foo::tests::does_foo_stuff ... ok
+",
+        );
+        assert_eq!(
+            render(Format::Grouped, &results),
+            "demo (lib)
+  ✔ it works
+  baz
+    ? is ignored (not yet)
+  foo
+    ✔ also does foo stuff
+    ✔ does foo stuff
+    bar
+      x does bar stuff
+cli (test)
+  foo
+    ✔ does foo stuff
+
+4 passed, 1 failed, 1 ignored in 0.00s
+"
+        );
+    }
+
+    #[test]
+    fn failure_output_is_shown_beneath_failed_test_up_to_the_line_limit() {
+        colored::control::set_override(false);
+        let mut result = parse_test_results("test it_fails ... FAILED").remove(0);
+        result.failure = Some("line 1\nline 2\nline 3".into());
+        assert_eq!(
+            render(Format::Flat, &[result]),
+            "x it fails
+    line 1
+    line 2
+    line 3
+
+0 passed, 1 failed, 0 ignored in 0.00s
+"
+        );
+        for (max_lines, want) in [
+            (Some(2), "    line 1\n    line 2\n    … (1 more line)\n"),
+            (Some(1), "    line 1\n    … (2 more lines)\n"),
+            (None, "    line 1\n    line 2\n    line 3\n"),
+        ] {
+            let mut out = Vec::new();
+            write_failure(&mut out, "line 1\nline 2\nline 3", "    ", max_lines, false).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), want, "{max_lines:?}");
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! A self-contained HTML report.
+
+use std::io::Write;
+
+use super::{escape, Reporter, Targets};
+use crate::{Status, Summary, TestResult};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; max-width: 60em; margin: 2em auto; padding: 0 1em; color: #222; }
+h2 { border-bottom: 1px solid #ddd; padding-bottom: 0.2em; }
+details { margin: 0.5em 0 0.5em 1em; }
+summary { cursor: pointer; font-weight: bold; color: #2a5db0; }
+ul { list-style: none; padding-left: 1em; }
+li { margin: 0.3em 0; }
+.badge { display: inline-block; min-width: 4.5em; padding: 0.1em 0.4em; border-radius: 0.3em; font-size: 0.8em; font-weight: bold; text-align: center; color: #fff; }
+.pass { background: #2e8b57; }
+.fail { background: #c0392b; }
+.ignored { background: #d4a017; }
+.flaky { background: #8e44ad; }
+.not-run { background: #7f8c8d; }
+code { color: #777; }
+pre { background: #f6f6f6; border-left: 3px solid #c0392b; padding: 0.5em; overflow-x: auto; }
+";
+
+/// Writes an HTML page with a section for each test binary, and a
+/// collapsible list of tests for each module, showing any failure output.
+pub(super) struct Html<W> {
+    pub(super) out: W,
+    pub(super) targets: Targets,
+}
+
+impl<W: Write> Reporter for Html<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.targets.insert(result);
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        let out = &mut self.out;
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html lang=\"en\">")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Test specification</title>")?;
+        writeln!(out, "<style>{STYLE}</style>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>Test specification</h1>")?;
+        writeln!(
+            out,
+            "<p>{} {} {} in {:.2}s</p>",
+            badge("pass", &format!("{} passed", summary.passed)),
+            badge("fail", &format!("{} failed", summary.failed)),
+            badge("ignored", &format!("{} ignored", summary.ignored)),
+            summary.duration.as_secs_f64(),
+        )?;
+        for (target, module) in &self.targets.0 {
+            writeln!(out, "<section>")?;
+            if let Some(target) = target {
+                writeln!(out, "<h2>{}</h2>", escape(&target.to_string()))?;
+            }
+            for (path, tests) in module.flatten() {
+                match path {
+                    Some(path) => {
+                        writeln!(out, "<details open>")?;
+                        writeln!(out, "<summary>{}</summary>", escape(&path))?;
+                        write_tests(out, tests)?;
+                        writeln!(out, "</details>")?;
+                    }
+                    None => write_tests(out, tests)?,
+                }
+            }
+            writeln!(out, "</section>")?;
+        }
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")?;
+        out.flush()
+    }
+}
+
+fn write_tests(out: &mut impl Write, tests: &[TestResult]) -> std::io::Result<()> {
+    writeln!(out, "<ul>")?;
+    for test in tests {
+        let status = match test.status {
+            Status::Pass => badge("pass", "pass"),
+            Status::Fail => badge("fail", "fail"),
+            Status::Ignored => badge("ignored", "ignored"),
+            Status::NotRun => badge("not-run", "not run"),
+        };
+        write!(out, "<li>{status} {}", escape(&test.name))?;
+        if let Some(location) = &test.location {
+            write!(out, " <code>{}</code>", escape(location))?;
+        }
+        if let Some(reason) = &test.ignore_reason {
+            write!(out, " <em>({})</em>", escape(reason))?;
+        }
+        if test.flaky {
+            write!(out, " {}", badge("flaky", "flaky"))?;
+        }
+        if let Some(failure) = &test.failure {
+            write!(out, "<pre>{}</pre>", escape(failure))?;
+        }
+        writeln!(out, "</li>")?;
+    }
+    writeln!(out, "</ul>")
+}
+
+fn badge(class: &str, text: &str) -> String {
+    format!("<span class=\"badge {class}\">{}</span>", escape(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format::tests::render, parse_test_results, Format};
+
+    #[test]
+    fn html_format_shows_each_module_with_failure_details() {
+        let mut results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test it_works ... ok
+test foo::tests::does_foo_stuff ... FAILED
+",
+        );
+        results[0].location = Some("src/lib.rs:12".into());
+        results[1].failure = Some("assertion `left == right` failed\n  left: <1>".into());
+        let html = render(Format::Html, &results);
+        for want in [
+            "<h2>demo (lib)</h2>",
+            "<li><span class=\"badge pass\">pass</span> it works <code>src/lib.rs:12</code></li>",
+            "<details open>\n<summary>foo</summary>",
+            "<span class=\"badge fail\">fail</span> does foo stuff<pre>assertion `left == right` failed\n  left: &lt;1&gt;</pre>",
+        ] {
+            assert!(html.contains(want), "missing {want:?} in:\n{html}");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! Machine-readable JSON output.
+
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, io::Write};
+
+use super::Reporter;
+use crate::{Summary, TestResult};
+
+/// Writes a single JSON document, once all the tests have finished:
+///
+/// ```json
+/// { "results": [ { "path": "tests::it_works", ... } ], "summary": { "passed": 1, ... } }
+/// ```
+pub(super) struct Json<W> {
+    pub(super) out: W,
+    pub(super) results: Vec<TestResult>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document<'a> {
+    results: Cow<'a, [TestResult]>,
+    summary: Cow<'a, Summary>,
+}
+
+impl<W: Write> Reporter for Json<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.results.push(result.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        let document = Document {
+            results: Cow::Borrowed(&self.results),
+            summary: Cow::Borrowed(summary),
+        };
+        serde_json::to_writer_pretty(&mut self.out, &document)?;
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+/// Writes one JSON object per line, as each test finishes, followed by the
+/// summary. Each object's `type` field says which it is:
+///
+/// ```json
+/// {"type":"test","path":"tests::it_works",...}
+/// {"type":"summary","passed":1,...}
+/// ```
+pub(super) struct JsonLines<W> {
+    pub(super) out: W,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Line<'a> {
+    Test(Cow<'a, TestResult>),
+    Summary(Cow<'a, Summary>),
+}
+
+impl<W: Write> JsonLines<W> {
+    fn write(&mut self, line: &Line) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.out, line)?;
+        writeln!(self.out)?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Reporter for JsonLines<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.write(&Line::Test(Cow::Borrowed(result)))
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        self.write(&Line::Summary(Cow::Borrowed(summary)))
+    }
+}
+
+/// Reads the results and summary from the output of the `json` or
+/// `json-lines` formats, or returns `None` if `text` isn't in either format.
+#[must_use]
+pub fn read_json(text: &str) -> Option<(Vec<TestResult>, Summary)> {
+    if let Ok(document) = serde_json::from_str::<Document>(text) {
+        return Some((document.results.into_owned(), document.summary.into_owned()));
+    }
+    let mut results = Vec::new();
+    let mut summary = None;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line).ok()? {
+            Line::Test(result) => results.push(result.into_owned()),
+            Line::Summary(read) => summary = Some(read.into_owned()),
+        }
+    }
+    Some((results, summary?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::read_json;
+    use crate::{format::tests::render, parse_test_results, Format, Summary};
+
+    #[test]
+    fn json_lines_format_writes_each_result_and_the_summary() {
+        let results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test foo::tests::does_foo_stuff ... ok
+",
+        );
+        assert_eq!(
+            render(Format::JsonLines, &results),
+            r#"{"type":"test","target":{"crate":"demo","name":"demo","kind":"lib","hash":"0123456789abcdef"},"path":"foo::tests::does_foo_stuff","module":"foo","name":"does foo stuff","status":"pass","duration":null,"failure":null,"ignore_reason":null,"flaky":false,"location":null}
+{"type":"summary","passed":1,"failed":0,"ignored":0,"not_run":0,"duration":0.0}
+"#
+        );
+    }
+
+    #[test]
+    fn json_format_output_can_be_read_back() {
+        let results = parse_test_results(
+            "test foo::tests::does_foo_stuff ... ok
+test it_fails ... FAILED
+",
+        );
+        let (read, _) = read_json(&render(Format::Json, &results)).unwrap();
+        assert_eq!(read, results);
+    }
+
+    #[test]
+    fn read_json_fn_reads_json_lines_output_but_not_other_text() {
+        let results = parse_test_results("test it_works ... ok\ntest it_fails ... FAILED\n");
+        let mut summary = Summary::default();
+        for result in &results {
+            summary.add(result);
+        }
+        assert_eq!(
+            read_json(&render(Format::JsonLines, &results)),
+            Some((results, summary))
+        );
+        assert_eq!(read_json("test it_works ... ok\n"), None);
+        assert_eq!(
+            read_json(r#"{ "type": "suite", "event": "started", "test_count": 1 }"#),
+            None
+        );
+    }
+}
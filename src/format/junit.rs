@@ -0,0 +1,135 @@
+//! A JUnit XML report, as understood by most CI systems.
+
+use std::io::Write;
+
+use super::{escape, Reporter, Targets};
+use crate::{Status, Summary, TestResult};
+
+/// Writes a `<testsuite>` for each test binary, with a `<testcase>` for each
+/// test. The test's sentence is used as its name, and its module as its
+/// class name. Its location, if known, is given as its `file` and `line`.
+pub(super) struct Junit<W> {
+    pub(super) out: W,
+    pub(super) targets: Targets,
+}
+
+impl<W: Write> Reporter for Junit<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.targets.insert(result);
+        Ok(())
+    }
+
+    fn finish(&mut self, summary: &Summary) -> std::io::Result<()> {
+        let out = &mut self.out;
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<testsuites name="cargo testdox" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+            summary.total(),
+            summary.failed,
+            summary.ignored + summary.not_run,
+            summary.duration.as_secs_f64(),
+        )?;
+        for (target, module) in &self.targets.0 {
+            let modules = module.flatten();
+            let tests: Vec<_> = modules.iter().flat_map(|(_, tests)| *tests).collect();
+            let count = |status: Status| tests.iter().filter(|t| t.status == status).count();
+            let name = target.as_ref().map(ToString::to_string).unwrap_or_default();
+            writeln!(
+                out,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" skipped="{}">"#,
+                escape(&name),
+                tests.len(),
+                count(Status::Fail),
+                count(Status::Ignored) + count(Status::NotRun),
+            )?;
+            for test in tests {
+                let classname = test
+                    .module
+                    .as_deref()
+                    .or(target.as_ref().map(|target| target.name.as_str()))
+                    .unwrap_or_default();
+                write!(
+                    out,
+                    r#"    <testcase name="{}" classname="{}""#,
+                    escape(&test.name),
+                    escape(classname),
+                )?;
+                if let Some(duration) = test.duration {
+                    write!(out, r#" time="{:.3}""#, duration.as_secs_f64())?;
+                }
+                if let Some((file, line)) = test.location.as_ref().and_then(|l| l.rsplit_once(':'))
+                {
+                    write!(out, r#" file="{}" line="{}""#, escape(file), escape(line))?;
+                }
+                match test.status {
+                    Status::Pass => writeln!(out, "/>")?,
+                    Status::Fail => {
+                        writeln!(out, ">")?;
+                        match &test.failure {
+                            Some(failure) => writeln!(
+                                out,
+                                r#"      <failure message="test failed">{}</failure>"#,
+                                escape(failure)
+                            )?,
+                            None => writeln!(out, r#"      <failure message="test failed"/>"#)?,
+                        }
+                        writeln!(out, "    </testcase>")?;
+                    }
+                    Status::Ignored | Status::NotRun => {
+                        writeln!(out, ">")?;
+                        let reason = match test.status {
+                            Status::NotRun => Some("not run"),
+                            _ => test.ignore_reason.as_deref(),
+                        };
+                        match reason {
+                            Some(reason) => {
+                                writeln!(out, r#"      <skipped message="{}"/>"#, escape(reason))?;
+                            }
+                            None => writeln!(out, "      <skipped/>")?,
+                        }
+                        writeln!(out, "    </testcase>")?;
+                    }
+                }
+            }
+            writeln!(out, "  </testsuite>")?;
+        }
+        writeln!(out, "</testsuites>")?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format::tests::render, parse_test_results, Format, Status};
+
+    #[test]
+    fn junit_format_reports_failures_and_skipped_tests() {
+        let mut results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test it_works ... ok
+test foo::tests::does_foo_stuff ... FAILED
+test foo::bar::tests::does_bar_stuff ... ignored, needs <database>
+",
+        );
+        let failed = results.iter_mut().find(|r| r.status == Status::Fail);
+        failed.unwrap().failure = Some("assertion failed: a < b".into());
+        results[0].location = Some("src/lib.rs:12".into());
+        assert_eq!(
+            render(Format::Junit, &results),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="cargo testdox" tests="3" failures="1" skipped="1" time="0.000">
+  <testsuite name="demo (lib)" tests="3" failures="1" skipped="1">
+    <testcase name="it works" classname="demo" file="src/lib.rs" line="12"/>
+    <testcase name="does foo stuff" classname="foo">
+      <failure message="test failed">assertion failed: a &lt; b</failure>
+    </testcase>
+    <testcase name="does bar stuff" classname="foo::bar">
+      <skipped message="needs &lt;database&gt;"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+}
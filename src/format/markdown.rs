@@ -0,0 +1,108 @@
+//! A Markdown specification document.
+
+use std::io::Write;
+
+use super::{Reporter, Targets};
+use crate::{Status, Summary, TestResult};
+
+/// Writes a heading for each test binary and module, followed by a checklist
+/// of its tests, ticking those that passed:
+///
+/// ```markdown
+/// # mycrate (lib)
+///
+/// ## parser
+///
+/// - [x] parses empty input
+/// - [ ] parses nested lists
+/// ```
+pub(super) struct Markdown<W> {
+    pub(super) out: W,
+    pub(super) targets: Targets,
+}
+
+impl<W: Write> Reporter for Markdown<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        self.targets.insert(result);
+        Ok(())
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> std::io::Result<()> {
+        let mut first = true;
+        let mut separate = |out: &mut W| {
+            if first {
+                first = false;
+                Ok(())
+            } else {
+                writeln!(out)
+            }
+        };
+        for (target, module) in &self.targets.0 {
+            if let Some(target) = target {
+                separate(&mut self.out)?;
+                writeln!(self.out, "# {target}")?;
+            }
+            for (path, tests) in module.flatten() {
+                if let Some(path) = path {
+                    separate(&mut self.out)?;
+                    writeln!(self.out, "## {path}")?;
+                }
+                separate(&mut self.out)?;
+                for test in tests {
+                    let name = match &test.location {
+                        Some(location) => format!("{} (`{location}`)", test.name),
+                        None => test.name.clone(),
+                    };
+                    let item = match (&test.status, &test.ignore_reason) {
+                        (Status::Pass, _) => format!("- [x] {name}"),
+                        (Status::Fail | Status::NotRun, _) => format!("- [ ] {name}"),
+                        (Status::Ignored, None) => format!("- [ ] {name} *(ignored)*"),
+                        (Status::Ignored, Some(reason)) => {
+                            format!("- [ ] {name} *(ignored: {reason})*")
+                        }
+                    };
+                    if test.flaky {
+                        writeln!(self.out, "{item} *(flaky)*")?;
+                    } else {
+                        writeln!(self.out, "{item}")?;
+                    }
+                }
+            }
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format::tests::render, parse_test_results, Format};
+
+    #[test]
+    fn markdown_format_writes_a_checklist_for_each_module() {
+        let mut results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test it_works ... ok
+test foo::tests::does_foo_stuff ... FAILED
+test foo::bar::tests::does_bar_stuff ... ignored
+test foo::also_does_foo_stuff ... ok
+",
+        );
+        results[0].location = Some("src/lib.rs:12".into());
+        assert_eq!(
+            render(Format::Markdown, &results),
+            "# demo (lib)
+
+- [x] it works (`src/lib.rs:12`)
+
+## foo
+
+- [x] also does foo stuff
+- [ ] does foo stuff
+
+## foo::bar
+
+- [ ] does bar stuff *(ignored)*
+"
+        );
+    }
+}
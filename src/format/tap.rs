@@ -0,0 +1,124 @@
+//! Test Anything Protocol (TAP) output.
+
+use std::io::Write;
+
+use super::Reporter;
+use crate::{symbols, Status, Summary, Target, TestResult};
+
+/// Writes a TAP version 14 stream, with a test point for each test as soon
+/// as it finishes, and the plan at the end:
+///
+/// ```text
+/// TAP version 14
+/// # mycrate (lib)
+/// ok 1 - foo – does foo stuff
+/// not ok 2 - it fails
+///   ---
+///   message: |
+///     assertion failed: false
+///   ...
+/// ok 3 - it is ignored # SKIP
+/// 1..3
+/// ```
+pub(super) struct Tap<W> {
+    pub(super) out: W,
+    /// Whether to use only ASCII characters.
+    pub(super) ascii: bool,
+    /// The number of test points written so far.
+    pub(super) count: usize,
+    /// The target whose comment was written most recently.
+    pub(super) target: Option<Target>,
+}
+
+impl<W: Write> Reporter for Tap<W> {
+    fn result(&mut self, result: &TestResult) -> std::io::Result<()> {
+        if self.count == 0 {
+            writeln!(self.out, "TAP version 14")?;
+        }
+        self.count += 1;
+        if result.target.is_some() && result.target != self.target {
+            self.target.clone_from(&result.target);
+            if let Some(target) = &self.target {
+                writeln!(self.out, "# {target}")?;
+            }
+        }
+        let mut description = match &result.module {
+            Some(module) => format!(
+                "{module} {} {}",
+                symbols::separator(self.ascii),
+                result.name
+            ),
+            None => result.name.clone(),
+        };
+        if let Some(location) = &result.location {
+            description = format!("{description} ({location})");
+        }
+        let description = description.replace('\\', "\\\\").replace('#', "\\#");
+        match result.status {
+            Status::Pass => writeln!(self.out, "ok {} - {description}", self.count)?,
+            Status::Fail => writeln!(self.out, "not ok {} - {description}", self.count)?,
+            Status::Ignored => match &result.ignore_reason {
+                Some(reason) => writeln!(
+                    self.out,
+                    "ok {} - {description} # SKIP {reason}",
+                    self.count
+                )?,
+                None => writeln!(self.out, "ok {} - {description} # SKIP", self.count)?,
+            },
+            Status::NotRun => {
+                writeln!(self.out, "ok {} - {description} # SKIP not run", self.count)?;
+            }
+        }
+        if let Some(failure) = &result.failure {
+            writeln!(self.out, "  ---")?;
+            writeln!(self.out, "  message: |")?;
+            for line in failure.lines() {
+                writeln!(self.out, "    {line}")?;
+            }
+            writeln!(self.out, "  ...")?;
+        }
+        self.out.flush()
+    }
+
+    fn finish(&mut self, _summary: &Summary) -> std::io::Result<()> {
+        if self.count == 0 {
+            writeln!(self.out, "TAP version 14")?;
+        }
+        writeln!(self.out, "1..{}", self.count)?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format::tests::render, parse_test_results, Format, Status};
+
+    #[test]
+    fn tap_format_writes_test_points_with_diagnostics_and_a_plan() {
+        let mut results = parse_test_results(
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+test foo::tests::does_foo_stuff ... ok
+test it_fails ... FAILED
+test tests::issue_42_is_ignored ... ignored
+",
+        );
+        let failed = results.iter_mut().find(|r| r.status == Status::Fail);
+        failed.unwrap().failure = Some("assertion failed: false\nnote: oops".into());
+        results[0].location = Some("src/foo.rs:30".into());
+        assert_eq!(
+            render(Format::Tap, &results),
+            "TAP version 14
+# demo (lib)
+ok 1 - foo – does foo stuff (src/foo.rs:30)
+ok 2 - issue 42 is ignored # SKIP
+not ok 3 - it fails
+  ---
+  message: |
+    assertion failed: false
+    note: oops
+  ...
+1..3
+"
+        );
+    }
+}
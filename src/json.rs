@@ -0,0 +1,99 @@
+//! Parsing for libtest's (unstable) JSON event format.
+
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::{test_result, Error, Result, Status, Summary, Target, TestResult};
+
+/// A single event emitted by libtest with `--format json`.
+///
+/// Only the fields testdox cares about are included; suite and benchmark
+/// events are deserialised too, but produce no results.
+#[derive(Deserialize)]
+struct Message {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    passed: Option<usize>,
+    failed: Option<usize>,
+    ignored: Option<usize>,
+    /// The reason an ignored test was ignored.
+    #[serde(rename = "message")]
+    reason: Option<String>,
+}
+
+/// Reports whether a line is one of libtest's JSON events, rather than, say,
+/// a test's own output that happens to start with `{`. Events always have a
+/// `type` field; a line that isn't valid JSON still counts as an event (so
+/// that it's reported as unparseable) if it looks like it has one.
+pub(crate) fn is_event(line: &str) -> bool {
+    if !line.starts_with('{') {
+        return false;
+    }
+    match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(value) => value.get("type").is_some(),
+        Err(_) => line.contains(r#""type":"#),
+    }
+}
+
+/// Parses a JSON event line, returning libtest's counts of results, and the
+/// time taken, if the event reports that a test suite (binary) has finished.
+pub(crate) fn parse_suite(line: &str) -> Option<Summary> {
+    let event: Message = serde_json::from_str(line).ok()?;
+    if event.kind != "suite" || event.event == "started" {
+        return None;
+    }
+    Some(Summary {
+        passed: event.passed.unwrap_or_default(),
+        failed: event.failed.unwrap_or_default(),
+        ignored: event.ignored.unwrap_or_default(),
+        duration: event
+            .exec_time
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .unwrap_or_default(),
+        ..Summary::default()
+    })
+}
+
+/// Parses a JSON event line, returning a `TestResult` if the event reports
+/// the outcome of a test, or [`Error::Unparseable`] if the line isn't a
+/// valid event.
+pub(crate) fn parse_event(line: &str) -> Result<Option<TestResult>> {
+    let event: Message =
+        serde_json::from_str(line).map_err(|_| Error::Unparseable(line.to_string()))?;
+    if event.kind != "test" {
+        return Ok(None);
+    }
+    let status = match event.event.as_str() {
+        "ok" => Status::Pass,
+        "failed" => Status::Fail,
+        "ignored" => Status::Ignored,
+        _ => return Ok(None),
+    };
+    let duration = event
+        .exec_time
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok());
+    let Some(name) = event.name else {
+        return Err(Error::Unparseable(line.to_string()));
+    };
+    // nextest identifies tests as `binary-id$test::path`.
+    let (target, test) = match name.split_once('$') {
+        Some((binary_id, test)) => (Some(Target::from_nextest_binary_id(binary_id)), test),
+        None => (None, name.as_str()),
+    };
+    let mut result = test_result(test, status, duration);
+    result.target = target;
+    if result.status == Status::Fail {
+        result.failure = event
+            .stdout
+            .map(|stdout| stdout.trim_matches('\n').to_string())
+            .filter(|stdout| !stdout.is_empty());
+    }
+    if result.status == Status::Ignored {
+        result.ignore_reason = event.reason;
+    }
+    Ok(Some(result))
+}
@@ -1,53 +1,160 @@
 #![doc = include_str!("../README.md")]
-use anyhow::{anyhow, Context};
 use colored::Colorize;
-use std::{fmt::Display, process::Command, str::FromStr};
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+mod error;
+mod format;
+mod json;
+mod log;
+mod merge;
+mod messages;
+mod nextest;
+mod parser;
+mod runner;
+#[cfg(feature = "scan")]
+mod scan;
+#[cfg(feature = "json")]
+mod seconds;
+mod summary;
+mod symbols;
+mod target;
+
+pub use error::{Error, Result};
+#[cfg(feature = "json")]
+pub use format::read_json;
+pub use format::{Format, Reporter};
+pub use log::{clean_line, read_log};
+pub use merge::merge;
+pub use parser::Parser;
+pub use runner::{libtest_json_supported, Runner, TestRun};
+#[cfg(feature = "scan")]
+pub use scan::{scan, NotATest};
+pub use summary::Summary;
+pub use target::{Target, TargetKind};
 
 #[must_use]
+#[deprecated(
+    since = "0.5.0",
+    note = "use `Runner::Cargo.spawn` to read the output as it's produced"
+)]
 /// Runs `cargo test` with any supplied extra arguments, and returns the
-/// resulting standard output.
+/// resulting output (standard output and standard error combined).
 ///
 /// # Panics
 ///
 /// If executing the `cargo test` command fails.
 pub fn get_cargo_test_output(extra_args: Vec<String>) -> String {
-    let mut cargo = Command::new("cargo");
-    cargo.arg("test");
-    cargo.args(extra_args);
-    let raw_output = cargo
-        .output()
-        .context(format!("{cargo:?}"))
-        .expect("executing command should succeed")
-        .stdout;
-    String::from_utf8_lossy(&raw_output).to_string()
+    let mut run = Runner::Cargo
+        .spawn(extra_args, false)
+        .expect("executing command should succeed");
+    let mut output = String::new();
+    for line in run.by_ref() {
+        output.push_str(&line.expect("reading output should succeed"));
+        output.push('\n');
+    }
+    run.wait().expect("waiting for command should succeed");
+    output
 }
 
 #[must_use]
 /// Parses the standard output of `cargo test` into a vec of `TestResult`.
+///
+/// Lines that look like test results, but can't be parsed, are skipped. To
+/// find out about them, use a [`Parser`], and its
+/// [`take_warnings`](Parser::take_warnings) method, instead.
 pub fn parse_test_results(test_output: &str) -> Vec<TestResult> {
-    test_output.lines().filter_map(parse_line).collect()
+    let mut parser = Parser::default();
+    let mut results = Vec::new();
+    for line in test_output.lines() {
+        match parser.parse_line(line) {
+            Ok(parsed) => results.extend(parsed),
+            Err(err) => parser.warn(err),
+        }
+    }
+    results.extend(parser.finish());
+    results
 }
 
-/// Parses a line from the standard output of `cargo test`.
+/// Parses a line from the output of `cargo test`.
+///
+/// The line may be either in libtest's usual human-readable format, or one of
+/// the JSON events it produces when given `--format json`.
 ///
 /// If the line represents the result of a test, returns `Some(TestResult)`,
 /// otherwise returns `None`.
-pub fn parse_line(line: impl AsRef<str>) -> Option<TestResult> {
-    let line = line.as_ref().strip_prefix("test ")?;
-    if line.starts_with("result") || line.contains("(line ") {
-        return None;
+///
+/// # Errors
+///
+/// If the line looks like a test result, but its status isn't recognised,
+/// or it looks like a JSON event, but isn't valid. Other lines starting with
+/// `{`, such as a test's own output, aren't events, and are ignored.
+pub fn parse_line(line: impl AsRef<str>) -> Result<Option<TestResult>> {
+    let line = line.as_ref();
+    if json::is_event(line) {
+        return json::parse_event(line);
     }
+    let Some(line) = line.strip_prefix("test ") else {
+        return Ok(None);
+    };
+    if line.starts_with("result") {
+        return Ok(None);
+    }
+    let Some((test, status)) = line.split_once(" ... ") else {
+        return Ok(None);
+    };
+    // Tests ignored with `#[ignore = "reason"]` are reported as
+    // `ignored, reason`.
+    let (status, ignore_reason) = match status.split_once(", ") {
+        Some(("ignored", reason)) => ("ignored", Some(reason.to_string())),
+        _ => (status, None),
+    };
+    let mut result = test_result(test, status.parse()?, None);
+    result.ignore_reason = ignore_reason;
+    Ok(Some(result))
+}
 
-    let (test, status) = line.split_once(" ... ")?;
-    let (module, name) = match test.rsplit_once("::") {
-        Some((module, name)) => (prettify_module(module), name),
-        None => (None, test),
+/// Builds a `TestResult` from the full path of a test (for example,
+/// `foo::tests::it_works`, or `src/lib.rs - foo (line 17)` for a doctest).
+pub(crate) fn test_result(test: &str, status: Status, duration: Option<Duration>) -> TestResult {
+    let (module, name) = match parse_doctest(test) {
+        Some(doctest) => doctest,
+        None => match test.rsplit_once("::") {
+            Some((module, name)) => (prettify_module(module), prettify(name)),
+            None => (None, prettify(test)),
+        },
     };
-    Some(TestResult {
+    TestResult {
+        target: None,
+        path: test.to_string(),
         module,
-        name: prettify(name),
-        status: status.parse().ok()?,
-    })
+        name,
+        status,
+        duration,
+        failure: None,
+        ignore_reason: None,
+        flaky: false,
+        location: None,
+    }
+}
+
+/// Parses the name of a doctest, such as `src/lib.rs - foo::bar (line 17)`,
+/// returning the path of the item it documents (if any), and a name for the
+/// example, such as `example (src/lib.rs:17)`.
+///
+/// Any attributes of the example, such as `compile fail`, are included in
+/// the name.
+fn parse_doctest(test: &str) -> Option<(Option<String>, String)> {
+    let (file, rest) = test.split_once(" - ")?;
+    let (item, rest) = rest.rsplit_once("(line ")?;
+    let (line, attributes) = rest.split_once(')')?;
+    let line: usize = line.parse().ok()?;
+    let item = item.trim();
+    let module = (!item.is_empty()).then(|| item.to_string());
+    let name = match attributes.trim_start_matches([' ', '-']) {
+        "" => format!("example ({file}:{line})"),
+        attributes => format!("example ({file}:{line}, {attributes})"),
+    };
+    Some((module, name))
 }
 
 #[must_use]
@@ -90,65 +197,165 @@ fn prettify_module(module: &str) -> Option<String> {
     Some(parts.join("::"))
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
 /// The (prettified) name and pass/fail status of a given test.
 pub struct TestResult {
+    /// The test binary the test belongs to, if known.
+    pub target: Option<Target>,
+    /// The full path of the test, as reported by the test runner (for
+    /// example, `foo::tests::it_works`).
+    pub path: String,
     pub module: Option<String>,
     pub name: String,
     pub status: Status,
+    /// How long the test took to run, if known.
+    #[cfg_attr(feature = "json", serde(default, with = "seconds::option"))]
+    pub duration: Option<Duration>,
+    /// For a failed test, any output it produced (such as a panic message),
+    /// if known.
+    pub failure: Option<String>,
+    /// For an ignored test, the reason given for ignoring it (with
+    /// `#[ignore = "reason"]`), if any.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub ignore_reason: Option<String>,
+    /// Whether the test both passed and failed, in different runs whose
+    /// results were [merged](merge()).
+    #[cfg_attr(feature = "json", serde(default))]
+    pub flaky: bool,
+    /// Where the test is defined, such as `src/lib.rs:12`, if known (that
+    /// is, if it was found by scanning the source code).
+    #[cfg_attr(feature = "json", serde(default))]
+    pub location: Option<String>,
 }
 
+impl TestResult {
+    /// Reports whether this is the result of a doctest (an example in the
+    /// documentation), rather than a test function.
+    #[must_use]
+    pub fn is_doctest(&self) -> bool {
+        parse_doctest(&self.path).is_some()
+    }
+}
+
+/// Shows the test's status and name, with its module, if any. The alternate
+/// form (`{:#}`) uses only ASCII characters, as for [`Status`].
 impl Display for TestResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self.module {
-            Some(module) => write!(
-                f,
-                "{} {} – {}",
-                self.status,
-                module.bright_blue(),
-                self.name
-            ),
-            None => write!(f, "{} {}", self.status, self.name),
+        let ascii = f.alternate();
+        if ascii {
+            write!(f, "{:#}", self.status)?;
+        } else {
+            write!(f, "{}", self.status)?;
+        }
+        if let Some(module) = &self.module {
+            write!(f, " {} {}", module.bright_blue(), symbols::separator(ascii))?;
+        }
+        write!(f, " {}{}", self.name, self.suffixes())
+    }
+}
+
+impl TestResult {
+    /// Returns what's shown after the test's name, each part in brackets:
+    /// why it was ignored, where it is, and whether it was flaky.
+    pub(crate) fn suffixes(&self) -> Suffixes<'_> {
+        Suffixes(self)
+    }
+}
+
+/// The suffixes shown after a test's name (see [`TestResult::suffixes`]).
+pub(crate) struct Suffixes<'a>(&'a TestResult);
+
+impl Display for Suffixes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(reason) = &self.0.ignore_reason {
+            write!(f, " ({})", reason.bright_yellow())?;
         }
+        if let Some(location) = &self.0.location {
+            write!(f, " ({})", location.dimmed())?;
+        }
+        if self.0.flaky {
+            write!(f, " ({})", "flaky".bright_magenta())?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 /// The status of a given test, as reported by `cargo test`.
+#[non_exhaustive]
 pub enum Status {
     Pass,
     Fail,
     Ignored,
+    /// The test was listed, but not run (see [`Parser::list`]).
+    #[cfg_attr(feature = "json", serde(rename = "not_run"))]
+    NotRun,
 }
 
 impl FromStr for Status {
-    type Err = anyhow::Error;
+    type Err = Error;
 
     fn from_str(status: &str) -> Result<Self, Self::Err> {
         match status {
             "ok" => Ok(Status::Pass),
             "FAILED" => Ok(Status::Fail),
             "ignored" => Ok(Status::Ignored),
-            _ => Err(anyhow!("unhandled test status {status:?}")),
+            _ => Err(Error::UnknownStatus(status.to_string())),
         }
     }
 }
 
+/// Shows the status as a symbol: `✔`, `x`, `?`, or `•`. The alternate form
+/// (`{:#}`) uses only ASCII characters instead, with `PASS`, `FAIL`, `SKIP`,
+/// or `TEST` markers, for terminals and log viewers that can't show Unicode
+/// symbols.
 impl Display for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let status = match self {
-            Status::Pass => "✔".bright_green(),
-            Status::Fail => "x".bright_red(),
-            Status::Ignored => "?".bright_yellow(),
+        let status = match (self, f.alternate()) {
+            (Status::Pass, false) => "✔".bright_green(),
+            (Status::Fail, false) => "x".bright_red(),
+            (Status::Ignored, false) => "?".bright_yellow(),
+            (Status::NotRun, false) => "•".dimmed(),
+            (Status::Pass, true) => "PASS".bright_green(),
+            (Status::Fail, true) => "FAIL".bright_red(),
+            (Status::Ignored, true) => "SKIP".bright_yellow(),
+            (Status::NotRun, true) => "TEST".dimmed(),
         };
         write!(f, "{status}")
     }
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
+    /// A result for the given test, with nothing else known about it.
+    pub(crate) fn result(
+        path: &str,
+        module: Option<&str>,
+        name: &str,
+        status: Status,
+    ) -> TestResult {
+        TestResult {
+            target: None,
+            path: path.into(),
+            module: module.map(Into::into),
+            name: name.into(),
+            status,
+            duration: None,
+            failure: None,
+            ignore_reason: None,
+            flaky: false,
+            location: None,
+        }
+    }
+
     #[test]
     fn prettify_returns_expected_results() {
         struct Case {
@@ -199,67 +406,191 @@ mod tests {
             },
             Case {
                 line: "test foo ... ok",
-                want: Some(TestResult {
-                    module: None,
-                    name: "foo".into(),
-                    status: Status::Pass,
-                }),
+                want: Some(result("foo", None, "foo", Status::Pass)),
             },
             Case {
                 line: "test foo::tests::does_foo_stuff ... ok",
-                want: Some(TestResult {
-                    module: Some("foo".into()),
-                    name: "does foo stuff".into(),
-                    status: Status::Pass,
-                }),
+                want: Some(result("foo::tests::does_foo_stuff", Some("foo"), "does foo stuff", Status::Pass)),
             },
             Case {
                 line: "test tests::urls_correctly_extracts_valid_urls ... FAILED",
+                want: Some(result("tests::urls_correctly_extracts_valid_urls", None, "urls correctly extracts valid urls", Status::Fail)),
+            },
+            Case {
+                line: "test files::test::files_can_be_sorted_in_descending_order ... ignored",
+                want: Some(result("files::test::files_can_be_sorted_in_descending_order", Some("files"), "files can be sorted in descending order", Status::Ignored)),
+            },
+            Case {
+                line: "test files::test::foo::tests::files_can_be_sorted_in_descending_order ... ignored",
+                want: Some(result("files::test::foo::tests::files_can_be_sorted_in_descending_order", Some("files::test::foo"), "files can be sorted in descending order", Status::Ignored)),
+            },
+            Case {
+                line: "test files::test_foo::files_can_be_sorted_in_descending_order ... ignored",
+                want: Some(result("files::test_foo::files_can_be_sorted_in_descending_order", Some("files::test_foo"), "files can be sorted in descending order", Status::Ignored)),
+            },
+            Case {
+                line: "test src/lib.rs - find_top_n_largest_files (line 17) ... ok",
+                want: Some(result("src/lib.rs - find_top_n_largest_files (line 17)", Some("find_top_n_largest_files"), "example (src/lib.rs:17)", Status::Pass)),
+            },
+            Case {
+                line: "test src/format.rs - format::Format::reporter (line 60) - compile fail ... FAILED",
+                want: Some(result("src/format.rs - format::Format::reporter (line 60) - compile fail", Some("format::Format::reporter"), "example (src/format.rs:60, compile fail)", Status::Fail)),
+            },
+            Case {
+                line: "test src/lib.rs - (line 1) ... ignored",
+                want: Some(result("src/lib.rs - (line 1)", None, "example (src/lib.rs:1)", Status::Ignored)),
+            },
+            Case {
+                line: "test db::tests::connects_to_server ... ignored, needs database",
                 want: Some(TestResult {
-                    module: None,
-                    name: "urls correctly extracts valid urls".into(),
-                    status: Status::Fail,
+                    ignore_reason: Some("needs database".into()),
+                    ..result("db::tests::connects_to_server", Some("db"), "connects to server", Status::Ignored)
                 }),
             },
             Case {
-                line: "test files::test::files_can_be_sorted_in_descending_order ... ignored",
+                line: "test output_format::_concise_expects ... ok",
+                want: Some(result("output_format::_concise_expects", Some("output_format"), "concise expects", Status::Pass)),
+            },
+        ]);
+        for case in cases {
+            assert_eq!(case.want, parse_line(case.line).unwrap());
+        }
+    }
+
+    #[test]
+    fn parse_line_fn_returns_errors_for_malformed_results() {
+        assert!(matches!(
+            parse_line("test foo ... exploded"),
+            Err(Error::UnknownStatus(status)) if status == "exploded"
+        ));
+        assert!(matches!(
+            parse_line(r#"{ "type": "test", "event": "ok" }"#),
+            Err(Error::Unparseable(_))
+        ));
+        assert!(matches!(
+            parse_line(r#"{ "type": "test", "name": "#),
+            Err(Error::Unparseable(line)) if line == r#"{ "type": "test", "name": "#
+        ));
+    }
+
+    #[test]
+    fn parse_test_results_fn_skips_lines_that_cannot_be_parsed() {
+        let results = parse_test_results(
+            r#"test a ... ok
+test b ... exploded
+{ "type": "test", "name":
+test c ... ok
+"#,
+        );
+        let paths: Vec<_> = results.iter().map(|result| result.path.as_str()).collect();
+        assert_eq!(paths, ["a", "c"]);
+    }
+
+    #[test]
+    fn parse_line_fn_ignores_test_output_that_is_not_a_json_event() {
+        for line in ["{ debug: 1 }", r#"{"answer": 42}"#, "{"] {
+            assert!(matches!(parse_line(line), Ok(None)), "{line}");
+        }
+    }
+
+    #[test]
+    fn parse_line_fn_understands_libtest_json_events() {
+        struct Case {
+            line: &'static str,
+            want: Option<TestResult>,
+        }
+        let cases = Vec::from([
+            Case {
+                line: r#"{ "type": "suite", "event": "started", "test_count": 1 }"#,
+                want: None,
+            },
+            Case {
+                line: r#"{ "type": "test", "event": "started", "name": "foo::tests::does_foo_stuff" }"#,
+                want: None,
+            },
+            Case {
+                line: r#"{ "type": "test", "name": "foo::tests::does_foo_stuff", "event": "ok", "exec_time": 0.25 }"#,
                 want: Some(TestResult {
-                    module: Some("files".into()),
-                    name: "files can be sorted in descending order".into(),
-                    status: Status::Ignored,
+                    duration: Some(Duration::from_millis(250)),
+                    ..result(
+                        "foo::tests::does_foo_stuff",
+                        Some("foo"),
+                        "does foo stuff",
+                        Status::Pass,
+                    )
                 }),
             },
             Case {
-                line: "test files::test::foo::tests::files_can_be_sorted_in_descending_order ... ignored",
+                line: r#"{ "type": "test", "name": "tests::it_fails", "event": "failed", "stdout": "oh no\n" }"#,
                 want: Some(TestResult {
-                    module: Some("files::test::foo".into()),
-                    name: "files can be sorted in descending order".into(),
-                    status: Status::Ignored,
+                    failure: Some("oh no".into()),
+                    ..result("tests::it_fails", None, "it fails", Status::Fail)
                 }),
             },
             Case {
-                line: "test files::test_foo::files_can_be_sorted_in_descending_order ... ignored",
+                line: r#"{ "type": "test", "name": "tests::needs_db", "event": "ignored", "message": "needs database" }"#,
                 want: Some(TestResult {
-                    module: Some("files::test_foo".into()),
-                    name: "files can be sorted in descending order".into(),
-                    status: Status::Ignored,
+                    ignore_reason: Some("needs database".into()),
+                    ..result("tests::needs_db", None, "needs db", Status::Ignored)
                 }),
             },
             Case {
-                line: "test src/lib.rs - find_top_n_largest_files (line 17) ... ok",
-                want: None,
+                line: r#"{ "type": "test", "name": "tests::ignored_test", "event": "ignored" }"#,
+                want: Some(result(
+                    "tests::ignored_test",
+                    None,
+                    "ignored test",
+                    Status::Ignored,
+                )),
             },
             Case {
-                line: "test output_format::_concise_expects ... ok",
+                line: r#"{ "type": "test", "name": "src/lib.rs - foo (line 17)", "event": "ok", "exec_time": 0.1 }"#,
                 want: Some(TestResult {
-                    module: Some("output_format".into()),
-                    name: "concise expects".into(),
-                    status: Status::Pass,
+                    duration: Some(Duration::from_millis(100)),
+                    ..result(
+                        "src/lib.rs - foo (line 17)",
+                        Some("foo"),
+                        "example (src/lib.rs:17)",
+                        Status::Pass,
+                    )
                 }),
             },
+            Case {
+                line: r#"{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.01 }"#,
+                want: None,
+            },
         ]);
         for case in cases {
-            assert_eq!(case.want, parse_line(case.line));
+            assert_eq!(case.want, parse_line(case.line).unwrap());
         }
     }
+
+    #[test]
+    fn is_doctest_fn_is_true_only_for_doctests() {
+        for (line, want) in [
+            ("test src/lib.rs - answer (line 5) ... ok", true),
+            (
+                "test src/format.rs - format::Format::reporter (line 60) - compile fail ... ok",
+                true,
+            ),
+            ("test foo::tests::it_works ... ok", false),
+            ("test it_works ... ok", false),
+        ] {
+            let result = parse_line(line).unwrap().unwrap();
+            assert_eq!(result.is_doctest(), want, "{line}");
+        }
+    }
+
+    #[test]
+    fn test_result_alternate_form_uses_only_ascii_characters() {
+        colored::control::set_override(false);
+        let result = result(
+            "foo::tests::it_works",
+            Some("foo"),
+            "it works",
+            Status::Pass,
+        );
+        assert_eq!(result.to_string(), "✔ foo – it works");
+        assert_eq!(format!("{result:#}"), "PASS foo - it works");
+    }
 }
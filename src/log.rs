@@ -0,0 +1,165 @@
+//! Reading test output from saved logs, such as those kept by CI systems.
+
+use std::io::BufRead;
+
+use crate::Result;
+
+/// Reads the lines of a saved log of test output, cleaned up with
+/// [`clean_line`], so that they can be parsed as if the tests were running.
+pub fn read_log(reader: impl BufRead) -> impl Iterator<Item = Result<String>> {
+    reader
+        .split(b'\n')
+        .map(|line| Ok(clean_line(&String::from_utf8_lossy(&line?))))
+}
+
+/// Removes anything from a line of a saved log that wasn't part of the
+/// original test output: carriage returns, ANSI escape codes (such as
+/// colours), and any timestamp added by the CI system, such as GitHub
+/// Actions' `2024-05-01T12:34:56.1234567Z`.
+#[must_use]
+pub fn clean_line(line: &str) -> String {
+    let line = strip_ansi(line.trim_end_matches(['\r', '\n']));
+    strip_timestamp(&line).to_string()
+}
+
+/// Removes ANSI escape sequences, such as `\x1b[32m`.
+fn strip_ansi(line: &str) -> String {
+    let mut clean = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            if c != '\r' {
+                clean.push(c);
+            }
+            continue;
+        }
+        match chars.next() {
+            // A control sequence, ending with a character in `@` to `~`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // An operating system command, such as a hyperlink, ending with
+            // BEL or `ESC \`.
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next() == Some('\\')) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    clean
+}
+
+/// Removes a timestamp from the start of a line, such as
+/// `2024-05-01T12:34:56.1234567Z ` or `[12:34:56] `, and the stream marker
+/// (such as `00O `) that GitLab adds after its timestamps.
+fn strip_timestamp(line: &str) -> &str {
+    let (rest, close) = match line.strip_prefix('[') {
+        Some(rest) => (rest, "] "),
+        None => (line, " "),
+    };
+    let Some((stamp, rest)) = rest.split_once(close) else {
+        return line;
+    };
+    if !is_timestamp(stamp) {
+        return line;
+    }
+    strip_gitlab_marker(rest)
+}
+
+/// Reports whether `stamp` is a time, such as `12:34:56`, or a date and time,
+/// such as `2024-05-01T12:34:56.789+01:00`.
+fn is_timestamp(stamp: &str) -> bool {
+    let shape: String = stamp
+        .chars()
+        .map(|c| if c.is_ascii_digit() { '9' } else { c })
+        .collect();
+    let rest = shape
+        .strip_prefix("9999-99-99T99:99:99")
+        .or_else(|| shape.strip_prefix("99:99:99"));
+    rest.is_some_and(|rest| {
+        rest.chars()
+            .all(|c| matches!(c, '9' | '.' | ':' | '+' | '-' | 'Z'))
+    })
+}
+
+/// Removes the stream marker GitLab adds after its timestamps, such as
+/// `00O ` (for standard output) or `01E+ ` (for standard error, continuing
+/// a partial line).
+fn strip_gitlab_marker(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let is_marker = bytes.len() >= 4
+        && bytes[..2].iter().all(u8::is_ascii_digit)
+        && matches!(bytes[2], b'O' | b'E');
+    if !is_marker {
+        return line;
+    }
+    let rest = &line[3..];
+    let rest = rest.strip_prefix('+').unwrap_or(rest);
+    rest.strip_prefix(' ').unwrap_or(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clean_line_fn_removes_everything_not_in_the_test_output() {
+        struct Case {
+            line: &'static str,
+            want: &'static str,
+        }
+        let cases = Vec::from([
+            Case {
+                line: "test tests::it_works ... ok",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "test tests::it_works ... ok\r\n",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "test tests::it_works ... \x1b[32mok\x1b[0m",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "\x1b[1m\x1b[92m     Running\x1b[0m unittests src/lib.rs",
+                want: "     Running unittests src/lib.rs",
+            },
+            Case {
+                line: "2024-05-01T12:34:56.1234567Z test tests::it_works ... ok",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "2024-05-01T12:34:56.123456Z 00O+ test tests::it_works ... ok",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "[2024-05-01T12:34:56.123+01:00]      Running tests/cli.rs",
+                want: "     Running tests/cli.rs",
+            },
+            Case {
+                line: "[12:34:56] test tests::it_works ... ok",
+                want: "test tests::it_works ... ok",
+            },
+            Case {
+                line: "12:34 is not a timestamp",
+                want: "12:34 is not a timestamp",
+            },
+            Case {
+                line: "[not a timestamp] test",
+                want: "[not a timestamp] test",
+            },
+        ]);
+        for case in cases {
+            assert_eq!(case.want, clean_line(case.line), "{:?}", case.line);
+        }
+    }
+}
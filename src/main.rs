@@ -1,16 +1,459 @@
-use cargo_testdox::{get_cargo_test_output, parse_test_results, Status};
+use anyhow::{anyhow, Context};
+#[cfg(feature = "json")]
+use cargo_testdox::read_json;
+#[cfg(feature = "scan")]
+use cargo_testdox::scan;
+use cargo_testdox::{
+    merge, read_log, Error, Format, Parser, Reporter, Runner, Summary, TestResult, TestRun,
+};
+use clap::Parser as _;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    str::FromStr,
+    time::Duration,
+};
 
-fn main() {
-    let output = get_cargo_test_output(std::env::args().skip(2).collect());
-    let results = parse_test_results(&output);
-    let mut failed = false;
-    for result in results {
-        println!("{result}");
-        if result.status == Status::Fail {
-            failed = true;
+/// Cargo invokes subcommands as `cargo-testdox testdox [ARGS]...`, so the
+/// first argument is the subcommand name.
+#[derive(clap::Parser)]
+#[command(name = "cargo", bin_name = "cargo")]
+enum Cargo {
+    Testdox(Testdox),
+}
+
+#[derive(clap::Args)]
+#[command(version, about, long_about = LONG_ABOUT)]
+#[allow(clippy::struct_excessive_bools, reason = "command-line flags")]
+struct Testdox {
+    /// The test runner to use: `cargo` (`cargo test`) or `nextest`
+    /// (`cargo nextest run`)
+    #[arg(long, value_name = "RUNNER", default_value = "cargo", global = true)]
+    runner: Runner,
+
+    /// How to show the results: `flat` (one line per test, shown as each test
+    /// finishes), `grouped` (grouped by module, shown at the end), `markdown`
+    /// (a Markdown specification document), `html` (an HTML report), `junit`
+    /// (a JUnit XML report), `tap` (a Test Anything Protocol stream), `json`
+    /// (a JSON document), or `json-lines` (a JSON object for each test, shown
+    /// as each test finishes)
+    #[arg(long, value_name = "FORMAT", default_value = "flat", global = true)]
+    format: Format,
+
+    /// Write the results to PATH, instead of standard output
+    #[arg(long, short, value_name = "PATH", global = true)]
+    output: Option<PathBuf>,
+
+    /// Read the test output from PATH (or `-` for standard input), such as a
+    /// saved CI log, instead of running the tests
+    #[arg(long, short, value_name = "PATH")]
+    input: Option<PathBuf>,
+
+    /// When to use colours: `auto` (only when writing to a terminal, unless
+    /// the `NO_COLOR` or `CLICOLOR` environment variables say otherwise),
+    /// `always`, or `never`
+    #[arg(long, value_name = "WHEN", default_value = "auto", global = true)]
+    color: Color,
+
+    /// Show results using only ASCII characters, with PASS, FAIL, and SKIP
+    /// markers instead of symbols
+    #[arg(long, global = true)]
+    ascii: bool,
+
+    /// Read libtest's JSON event output, where the toolchain supports it
+    /// (nightly only, for `cargo test`)
+    #[arg(long)]
+    libtest_json: bool,
+
+    /// Show at most N lines of output for each failing test
+    #[arg(long, value_name = "N", global = true)]
+    failure_lines: Option<usize>,
+
+    /// Don't show the results of doctests (the examples in your
+    /// documentation)
+    #[arg(long, global = true)]
+    no_doctests: bool,
+
+    /// Fail if any of the test output couldn't be understood, or if any
+    /// results seem to be missing, instead of just showing a warning
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// List the tests as sentences, without running them, which is much
+    /// faster for generating a specification document (`cargo` runner only)
+    #[arg(long)]
+    list: bool,
+
+    /// Find the tests by reading the source code, without building or
+    /// running them, and show where each one is defined
+    #[cfg(feature = "scan")]
+    #[arg(long, conflicts_with_all = ["input", "list"])]
+    scan: bool,
+
+    /// Arguments passed on to the test runner, such as `--release` or a test
+    /// name filter
+    #[arg(value_name = "ARGS", allow_hyphen_values = true)]
+    runner_args: Vec<String>,
+
+    /// Arguments passed on to the test binaries, such as `--include-ignored`
+    #[arg(value_name = "TEST_ARGS", last = true)]
+    test_args: Vec<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Merge the results of several test runs, such as the shards of a test
+    /// suite run by separate CI jobs, into one report
+    ///
+    /// A test that appears in more than one run is only shown once. If it
+    /// failed in any run, it's shown as failed, and if it also passed in
+    /// another run, it's marked as flaky.
+    Merge {
+        /// Saved logs of test output, or the output of `--format json` or
+        /// `--format json-lines`
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+const LONG_ABOUT: &str = "\
+Runs your tests and prints their names as sentences.
+
+Any arguments other than cargo-testdox's own options (which can appear in any \
+order) are passed on to the test runner (`cargo test`, or `cargo nextest run` \
+with `--runner nextest`), and any arguments after `--` are passed on to the \
+test binaries themselves. For example:
+
+    cargo testdox --release --format grouped -- --include-ignored";
+
+/// When to use colours in the output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Color {
+    /// When writing to a terminal, unless the environment says otherwise.
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(color: &str) -> Result<Self, Self::Err> {
+        match color {
+            "auto" => Ok(Color::Auto),
+            "always" => Ok(Color::Always),
+            "never" => Ok(Color::Never),
+            _ => Err(anyhow!("unknown color choice {color:?}")),
+        }
+    }
+}
+
+impl Testdox {
+    /// Returns the arguments for the test runner, including any arguments for
+    /// the test binaries after a `--` separator.
+    fn runner_args(&self) -> Vec<String> {
+        let mut args = self.runner_args.clone();
+        if !self.test_args.is_empty() {
+            args.push("--".into());
+            args.extend(self.test_args.iter().cloned());
+        }
+        args
+    }
+
+    /// Reports whether a result should be shown, rather than hidden by one
+    /// of the options.
+    fn shows(&self, result: &TestResult) -> bool {
+        !(self.no_doctests && result.is_doctest())
+    }
+}
+
+/// How a run of `cargo testdox` ended. Each outcome has its own exit code,
+/// so that scripts can tell them apart. (Exit code 2 is used by clap for
+/// invalid arguments.)
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Outcome {
+    /// All the tests passed.
+    Passed = 0,
+    /// Some tests failed, the test process failed some other way (for
+    /// example, by crashing), or no tests ran.
+    TestsFailed = 1,
+    /// The tests couldn't be built.
+    BuildFailed = 3,
+    /// Something went wrong in `cargo testdox` itself, such as not being able
+    /// to run cargo, or write the results, or (with `--strict`) some of the
+    /// test output couldn't be understood. This is also the outcome if the
+    /// test command fails for some reason other than a build error before
+    /// any tests run, such as an argument cargo doesn't accept.
+    Error = 4,
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        ExitCode::from(outcome as u8)
+    }
+}
+
+fn main() -> ExitCode {
+    let Cargo::Testdox(args) = Cargo::parse_from(testdox_options_first(std::env::args()));
+    match testdox(&args) {
+        Ok(outcome) => outcome.into(),
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            Outcome::Error.into()
+        }
+    }
+}
+
+/// Moves cargo-testdox's own options (and their values) ahead of any
+/// arguments for the test runner, so that they're recognised wherever they
+/// appear, as in `cargo testdox --lib --format grouped`. Otherwise,
+/// everything from the first runner argument on would be passed to the
+/// runner. Arguments after `--` are left alone, and so is `--help` after a
+/// command such as `merge`, so that it shows the help for that command.
+fn testdox_options_first(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let command = <Testdox as clap::Args>::augment_args(clap::Command::new("testdox"));
+    let mut args = args.into_iter();
+    // The program name, and the `testdox` subcommand name.
+    let mut ours: Vec<String> = args.by_ref().take(2).collect();
+    let mut theirs = Vec::new();
+    let mut subcommand = false;
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            theirs.push(arg);
+            theirs.extend(args.by_ref());
+            break;
+        }
+        if theirs.is_empty() && command.find_subcommand(&arg).is_some() {
+            subcommand = true;
+            theirs.push(arg);
+            continue;
+        }
+        match option(&command, &arg) {
+            Some(_) if subcommand && matches!(arg.as_str(), "-h" | "--help") => theirs.push(arg),
+            Some(takes_value) => {
+                ours.push(arg);
+                if takes_value {
+                    ours.extend(args.next());
+                }
+            }
+            None => theirs.push(arg),
+        }
+    }
+    ours.extend(theirs);
+    ours
+}
+
+/// Reports whether `arg` is one of cargo-testdox's own options, and if so,
+/// whether its value is the next argument (rather than being given as, say,
+/// `--format=grouped`).
+fn option(command: &clap::Command, arg: &str) -> Option<bool> {
+    if let Some(long) = arg.strip_prefix("--") {
+        let (name, inline) = long
+            .split_once('=')
+            .map_or((long, false), |(name, _)| (name, true));
+        if name == "help" || name == "version" {
+            return Some(false);
+        }
+        let option = command
+            .get_arguments()
+            .find(|option| option.get_long() == Some(name))?;
+        return Some(option.get_action().takes_values() && !inline);
+    }
+    let mut chars = arg.strip_prefix('-')?.chars();
+    let short = chars.next()?;
+    let inline = !chars.as_str().is_empty();
+    if matches!(short, 'h' | 'V') && !inline {
+        return Some(false);
+    }
+    let option = command
+        .get_arguments()
+        .find(|option| option.get_short() == Some(short))?;
+    let takes_value = option.get_action().takes_values();
+    // Something like `-ab` is a group of cargo's flags, not ours.
+    if inline && !takes_value {
+        return None;
+    }
+    Some(takes_value && !inline)
+}
+
+fn testdox(args: &Testdox) -> anyhow::Result<Outcome> {
+    match args.color {
+        // `colored` already checks the environment, and whether standard
+        // output is a terminal.
+        Color::Auto if args.output.is_none() => {}
+        Color::Auto | Color::Never => colored::control::set_override(false),
+        Color::Always => colored::control::set_override(true),
+    }
+    if let Some(Command::Merge { files }) = &args.command {
+        return merge_runs(args, files);
+    }
+    #[cfg(feature = "scan")]
+    if args.scan {
+        let mut reporter = reporter(args)?;
+        let mut summary = Summary::default();
+        let (results, warnings) = scan(Path::new("."))?;
+        report(reporter.as_mut(), &mut summary, args, results)?;
+        reporter.finish(&summary)?;
+        for warning in &warnings {
+            eprintln!("warning: {warning}");
         }
+        return Ok(outcome(args, &summary, warnings.len()));
     }
-    if failed {
-        std::process::exit(1);
+    let mut run = None;
+    let lines: Box<dyn Iterator<Item = cargo_testdox::Result<String>>> = match &args.input {
+        Some(path) if path.as_os_str() == "-" => Box::new(read_log(std::io::stdin().lock())),
+        Some(path) => {
+            let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+            Box::new(read_log(BufReader::new(file)))
+        }
+        None if args.list => Box::new(run.insert(args.runner.list(args.runner_args())?)),
+        None => Box::new(run.insert(args.runner.spawn(args.runner_args(), args.libtest_json)?)),
+    };
+    let mut reporter = reporter(args)?;
+    let mut parser = if args.list {
+        Parser::list(args.runner)
+    } else {
+        Parser::new(args.runner)
+    };
+    let mut summary = Summary::default();
+    let mut warnings = 0;
+    for line in lines {
+        let results = parse_line(&mut parser, &line?, &mut warnings);
+        report(reporter.as_mut(), &mut summary, args, results)?;
+    }
+    report(reporter.as_mut(), &mut summary, args, parser.finish())?;
+    // When reading a saved log, there's no exit status to check.
+    let status = run.map(TestRun::wait).transpose()?;
+    match status.map(|status| parser.check_build(status)) {
+        Some(Err(Error::BuildFailed { status, output })) => {
+            for line in output {
+                eprintln!("{line}");
+            }
+            eprintln!("Error: build failed ({status})");
+            return Ok(Outcome::BuildFailed);
+        }
+        Some(Err(Error::CommandFailed { status, output })) => {
+            for line in output {
+                eprintln!("{line}");
+            }
+            eprintln!("Error: test command failed ({status})");
+            return Ok(Outcome::Error);
+        }
+        _ => {}
+    }
+    summary.duration = parser.duration();
+    reporter.finish(&summary)?;
+    let outcome = outcome(args, &summary, warnings);
+    if let Some(status) = status.filter(|status| !status.success()) {
+        if outcome == Outcome::Passed {
+            eprintln!("Error: test process failed ({status})");
+            return Ok(Outcome::TestsFailed);
+        }
+    }
+    Ok(outcome)
+}
+
+/// Reads the results of several test runs, and reports them merged into one.
+fn merge_runs(args: &Testdox, files: &[PathBuf]) -> anyhow::Result<Outcome> {
+    let mut runs = Vec::new();
+    // The runs were probably at the same time, so the longest is the time
+    // taken overall.
+    let mut duration = Duration::ZERO;
+    let mut warnings = 0;
+    for path in files {
+        let (results, run_duration) = read_run(args, path, &mut warnings)?;
+        runs.push(results);
+        duration = duration.max(run_duration);
+    }
+    let mut reporter = reporter(args)?;
+    let mut summary = Summary::default();
+    report(reporter.as_mut(), &mut summary, args, merge(runs))?;
+    summary.duration = duration;
+    reporter.finish(&summary)?;
+    Ok(outcome(args, &summary, warnings))
+}
+
+/// Reads the results of a test run, and the time it took, from a saved log
+/// of its output, or from the output of the `json` or `json-lines` formats.
+fn read_run(
+    args: &Testdox,
+    path: &Path,
+    warnings: &mut usize,
+) -> anyhow::Result<(Vec<TestResult>, Duration)> {
+    let text = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let text = String::from_utf8_lossy(&text);
+    #[cfg(feature = "json")]
+    if let Some((results, summary)) = read_json(&text) {
+        return Ok((results, summary.duration));
+    }
+    let mut parser = Parser::new(args.runner);
+    let mut results = Vec::new();
+    for line in read_log(text.as_bytes()) {
+        results.extend(parse_line(&mut parser, &line?, warnings));
+    }
+    results.extend(parser.finish());
+    Ok((results, parser.duration()))
+}
+
+/// Creates the reporter for the chosen format, writing to the chosen output.
+fn reporter(args: &Testdox) -> anyhow::Result<Box<dyn Reporter>> {
+    let out: Box<dyn Write> = match &args.output {
+        Some(path) => {
+            let file =
+                File::create(path).with_context(|| format!("creating {}", path.display()))?;
+            Box::new(BufWriter::new(file))
+        }
+        None => Box::new(std::io::stdout()),
+    };
+    Ok(args.format.reporter(out, args.failure_lines, args.ascii))
+}
+
+/// Parses a line of test output, showing (and counting) any warnings.
+fn parse_line(parser: &mut Parser, line: &str, warnings: &mut usize) -> Vec<TestResult> {
+    let results = parser.parse_line(line).unwrap_or_else(|err| {
+        parser.warn(err);
+        Vec::new()
+    });
+    for warning in parser.take_warnings() {
+        eprintln!("warning: {warning}");
+        *warnings += 1;
+    }
+    results
+}
+
+/// Decides the outcome, once all the results have been reported.
+fn outcome(args: &Testdox, summary: &Summary, warnings: usize) -> Outcome {
+    if args.strict && warnings > 0 {
+        let noun = if warnings == 1 { "warning" } else { "warnings" };
+        eprintln!("Error: {warnings} {noun} about the test output (with --strict)");
+        return Outcome::Error;
+    }
+    if summary.total() == 0 {
+        eprintln!("Error: no tests ran");
+        return Outcome::TestsFailed;
+    }
+    if summary.failed > 0 {
+        return Outcome::TestsFailed;
+    }
+    Outcome::Passed
+}
+
+/// Passes each result that should be shown to the reporter, and counts it
+/// towards the summary.
+fn report(
+    reporter: &mut dyn Reporter,
+    summary: &mut Summary,
+    args: &Testdox,
+    results: Vec<TestResult>,
+) -> std::io::Result<()> {
+    for result in results.into_iter().filter(|result| args.shows(result)) {
+        reporter.result(&result)?;
+        summary.add(&result);
     }
+    Ok(())
 }
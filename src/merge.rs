@@ -0,0 +1,132 @@
+//! Combining the results of several test runs into one.
+
+use std::collections::HashMap;
+
+use crate::{Status, Target, TestResult};
+
+/// Combines the results of several test runs, such as the shards of a test
+/// suite run by separate CI jobs, into one list of results.
+///
+/// A test that appears in more than one run (identified by its target and
+/// full path) is only included once, in the position where it first
+/// appeared. If its status differs between runs, a failure wins over a pass,
+/// which wins over being ignored. A test that both passed and failed is
+/// marked as [flaky](TestResult::flaky).
+pub fn merge(runs: impl IntoIterator<Item = Vec<TestResult>>) -> Vec<TestResult> {
+    let mut merged: Vec<TestResult> = Vec::new();
+    let mut seen: HashMap<(Option<Target>, String), usize> = HashMap::new();
+    for result in runs.into_iter().flatten() {
+        let key = (result.target.clone(), result.path.clone());
+        let Some(&index) = seen.get(&key) else {
+            seen.insert(key, merged.len());
+            merged.push(result);
+            continue;
+        };
+        let existing = &mut merged[index];
+        let flaky = existing.flaky
+            || result.flaky
+            || matches!(
+                (&existing.status, &result.status),
+                (Status::Pass, Status::Fail) | (Status::Fail, Status::Pass)
+            );
+        if rank(&result.status) > rank(&existing.status) {
+            *existing = result;
+        }
+        existing.flaky = flaky;
+    }
+    merged
+}
+
+/// Which status wins when the same test has different statuses in different
+/// runs: the highest ranked.
+fn rank(status: &Status) -> u8 {
+    match status {
+        Status::NotRun => 0,
+        Status::Ignored => 1,
+        Status::Pass => 2,
+        Status::Fail => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::result;
+
+    #[test]
+    fn merge_fn_includes_each_test_once_and_fail_wins() {
+        let merged = merge([
+            Vec::from([
+                result("a", None, "a", Status::Pass),
+                result("b", None, "b", Status::Fail),
+                result("c", None, "c", Status::Ignored),
+            ]),
+            Vec::from([
+                result("b", None, "b", Status::Pass),
+                result("c", None, "c", Status::Pass),
+                result("d", None, "d", Status::Pass),
+                result("a", None, "a", Status::Pass),
+            ]),
+            Vec::from([result("a", None, "a", Status::Fail)]),
+        ]);
+        let summary: Vec<_> = merged
+            .iter()
+            .map(|result| (result.path.as_str(), &result.status, result.flaky))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("a", &Status::Fail, true),
+                ("b", &Status::Fail, true),
+                ("c", &Status::Pass, false),
+                ("d", &Status::Pass, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_fn_keeps_tests_with_the_same_path_in_different_targets() {
+        let lib = Target {
+            krate: Some("demo".into()),
+            name: "demo".into(),
+            kind: crate::TargetKind::Lib,
+            hash: None,
+        };
+        let merged = merge([Vec::from([
+            TestResult {
+                target: Some(lib),
+                ..result("tests::it_works", None, "it works", Status::Pass)
+            },
+            result("tests::it_works", None, "it works", Status::Pass),
+        ])]);
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn merge_fn_keeps_tests_in_same_named_targets_of_different_crates() {
+        let cli = |krate: &str, hash: &str, status| TestResult {
+            target: Some(Target {
+                krate: Some(krate.into()),
+                name: "cli".into(),
+                kind: crate::TargetKind::Test,
+                hash: Some(hash.into()),
+            }),
+            ..result("cli_works", None, "cli works", status)
+        };
+        let merged = merge([
+            Vec::from([cli("foo", "b649f6ea8b966137", Status::Pass)]),
+            Vec::from([cli("bar_baz", "19da1b0f0ec8cbd3", Status::Fail)]),
+        ]);
+        let summary: Vec<_> = merged
+            .iter()
+            .map(|result| (result.target.as_ref().unwrap().to_string(), &result.status))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("foo::cli (test)".to_string(), &Status::Pass),
+                ("bar_baz::cli (test)".to_string(), &Status::Fail),
+            ]
+        );
+    }
+}
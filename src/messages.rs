@@ -0,0 +1,138 @@
+//! Parsing cargo's own JSON messages (with `--message-format json`), which
+//! say which package each test binary was built from.
+
+use serde::Deserialize;
+
+/// A message printed by cargo with `--message-format json`.
+///
+/// Only the fields testdox cares about are included.
+#[derive(Deserialize)]
+struct Message {
+    reason: String,
+    package_id: Option<String>,
+    executable: Option<String>,
+    #[serde(rename = "message")]
+    diagnostic: Option<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    rendered: Option<String>,
+}
+
+/// What a line of cargo's JSON output says.
+#[derive(Debug, PartialEq)]
+pub(crate) enum CargoMessage {
+    /// A test binary was built (or was already up to date): `hash` is the
+    /// hash in its file name, and `krate` the crate it belongs to.
+    Executable { hash: String, krate: String },
+    /// A compiler diagnostic, such as an error or a warning, rendered as it
+    /// would be shown without `--message-format json`.
+    Diagnostic(String),
+    /// Anything else, such as a library being built.
+    Other,
+}
+
+/// Parses a line of cargo's output, returning `None` if it isn't one of
+/// cargo's JSON messages (which always have a `reason` field).
+pub(crate) fn parse_message(line: &str) -> Option<CargoMessage> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let message: Message = serde_json::from_str(line).ok()?;
+    Some(match message.reason.as_str() {
+        "compiler-artifact" => {
+            let executable = |message: &Message| {
+                let path = message.executable.as_deref()?;
+                let file = path.rsplit(['/', '\\']).next()?;
+                let file = file.strip_suffix(".exe").unwrap_or(file);
+                let (_, hash) = file.rsplit_once('-')?;
+                let krate = package_name(message.package_id.as_deref()?)?;
+                Some(CargoMessage::Executable {
+                    hash: hash.to_string(),
+                    krate: krate.replace('-', "_"),
+                })
+            };
+            executable(&message).unwrap_or(CargoMessage::Other)
+        }
+        "compiler-message" => message
+            .diagnostic
+            .and_then(|diagnostic| diagnostic.rendered)
+            .map_or(CargoMessage::Other, CargoMessage::Diagnostic),
+        _ => CargoMessage::Other,
+    })
+}
+
+/// Returns the package name from a package ID, such as
+/// `path+file:///home/user/foo#0.1.0`, `path+file:///home/user/foo#bar@0.1.0`,
+/// or (with cargo before 1.77) `foo 0.1.0 (path+file:///home/user/foo)`.
+fn package_name(package_id: &str) -> Option<&str> {
+    let Some((url, fragment)) = package_id.rsplit_once('#') else {
+        return package_id.split_once(' ').map(|(name, _)| name);
+    };
+    match fragment.split_once('@') {
+        Some((name, _version)) => Some(name),
+        // The name is left out when it's the same as the last part of the
+        // package's URL.
+        None if fragment.starts_with(|c: char| c.is_ascii_digit()) => {
+            let path = url.split('?').next()?.trim_end_matches('/');
+            path.rsplit('/').next()
+        }
+        None => Some(fragment),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_message_fn_returns_expected_result() {
+        let cases = [
+            (
+                r#"{"reason":"compiler-artifact","package_id":"path+file:///tmp/ws/bar-baz#0.1.0","manifest_path":"/tmp/ws/bar-baz/Cargo.toml","target":{"kind":["test"],"crate_types":["bin"],"name":"cli","src_path":"/tmp/ws/bar-baz/tests/cli.rs","edition":"2021","doc":false,"doctest":false,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/tmp/ws/target/debug/deps/cli-19da1b0f0ec8cbd3"],"executable":"/tmp/ws/target/debug/deps/cli-19da1b0f0ec8cbd3","fresh":false}"#,
+                Some(CargoMessage::Executable {
+                    hash: "19da1b0f0ec8cbd3".into(),
+                    krate: "bar_baz".into(),
+                }),
+            ),
+            (
+                r#"{"reason":"compiler-artifact","package_id":"path+file:///tmp/ws/foo#0.1.0","manifest_path":"/tmp/ws/foo/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"foo","src_path":"/tmp/ws/foo/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":["/tmp/ws/target/debug/deps/libfoo-c38e09994eaa7082.rlib","/tmp/ws/target/debug/deps/libfoo-c38e09994eaa7082.rmeta"],"executable":null,"fresh":false}"#,
+                Some(CargoMessage::Other),
+            ),
+            (
+                r#"{"reason":"compiler-message","package_id":"path+file:///tmp/ws/foo#0.1.0","message":{"rendered":"error[E0308]: mismatched types\n","level":"error"}}"#,
+                Some(CargoMessage::Diagnostic(
+                    "error[E0308]: mismatched types\n".into(),
+                )),
+            ),
+            (
+                r#"{"reason":"build-finished","success":true}"#,
+                Some(CargoMessage::Other),
+            ),
+            (
+                r#"{ "type": "test", "event": "ok", "name": "it_works" }"#,
+                None,
+            ),
+            ("   Compiling foo v0.1.0 (/tmp/ws/foo)", None),
+        ];
+        for (line, want) in cases {
+            assert_eq!(parse_message(line), want, "{line}");
+        }
+    }
+
+    #[test]
+    fn package_name_fn_reads_the_name_from_a_package_id() {
+        for (package_id, want) in [
+            ("path+file:///tmp/ws/bar-baz#0.1.0", Some("bar-baz")),
+            ("path+file:///tmp/ws/crates/foo#bar@0.1.0", Some("bar")),
+            (
+                "registry+https://github.com/rust-lang/crates.io-index#serde@1.0.219",
+                Some("serde"),
+            ),
+            ("foo 0.1.0 (path+file:///tmp/ws/foo)", Some("foo")),
+        ] {
+            assert_eq!(package_name(package_id), want, "{package_id}");
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Parsing for the human-readable output of `cargo nextest run`.
+
+use std::time::Duration;
+
+use crate::{json, test_result, Result, Status, Target, TestResult};
+
+/// Parses a line of `cargo nextest` output, such as:
+///
+/// ```text
+///         PASS [   0.012s] mycrate tests::it_works
+/// ```
+///
+/// If the line represents the result of a test, returns `Some(TestResult)`,
+/// otherwise returns `None`. When tests are retried, each attempt (such as
+/// `TRY 1 FAIL [   0.012s] ...`) is a result; it's up to the caller to
+/// discard the failed attempts that are retried (see [`parse_attempt`]).
+/// Lines in nextest's libtest-json message format are also understood.
+///
+/// Only JSON lines can produce an error, if they aren't valid events.
+pub(crate) fn parse_line(line: &str) -> Result<Option<TestResult>> {
+    let line = line.trim_start();
+    if json::is_event(line) {
+        return json::parse_event(line);
+    }
+    Ok(parse_status_line(line))
+}
+
+fn parse_status_line(line: &str) -> Option<TestResult> {
+    let (status, rest) = line.split_once(" [")?;
+    let status = match status.rsplit_once(' ') {
+        Some((attempt, status)) if attempt.starts_with("TRY ") => parse_status(status)?,
+        Some(_) => return None,
+        None => parse_status(status)?,
+    };
+    let (duration, rest) = rest.split_once("] ")?;
+    let duration = parse_duration(duration);
+    let (target, test) = parse_test(rest)?;
+    let mut result = test_result(&test, status, duration);
+    result.target = Some(target);
+    Some(result)
+}
+
+/// Parses a line about an attempt at running a test that's retried on
+/// failure, such as `TRY 1 FAIL [   0.012s] mycrate tests::flaky`, or
+/// `RETRY 2/3 [         ] mycrate tests::flaky`, returning the test's target
+/// and path. Once there's such a line for a test, any earlier failed attempt
+/// has been superseded.
+pub(crate) fn parse_attempt(line: &str) -> Option<(Target, String)> {
+    let (status, rest) = line.trim_start().split_once(" [")?;
+    if !status.starts_with("TRY ") && !status.starts_with("RETRY ") {
+        return None;
+    }
+    let (_, rest) = rest.split_once("] ")?;
+    parse_test(rest)
+}
+
+/// Parses the binary ID and test path that end a status line, such as
+/// `(3/42) mycrate tests::it_works`.
+fn parse_test(rest: &str) -> Option<(Target, String)> {
+    let (binary_id, test) = strip_progress(rest).split_once(' ')?;
+    Some((
+        Target::from_nextest_binary_id(binary_id),
+        test.trim().to_string(),
+    ))
+}
+
+/// Reports whether a line is one of nextest's test status lines, such as
+/// `PASS [   0.012s] ...`, `TRY 1 FAIL [   0.012s] ...`, or `SLOW [> 60.000s] ...`.
+pub(crate) fn is_status_line(line: &str) -> bool {
+    line.trim_start()
+        .split_once(" [")
+        .is_some_and(|(status, _)| {
+            !status.is_empty()
+                && status.chars().all(|c| {
+                    c.is_ascii_uppercase() || c.is_ascii_digit() || c == ' ' || c == '-' || c == '/'
+                })
+        })
+}
+
+/// Reports whether a line introduces the captured output of a failed test,
+/// such as `──── STDERR:  mycrate tests::it_fails`.
+pub(crate) fn is_output_header(line: &str) -> bool {
+    let line = line.trim_start_matches(['─', '-', ' ']);
+    ["STDOUT:", "STDERR:", "OUTPUT:"]
+        .iter()
+        .any(|header| line.starts_with(header))
+}
+
+/// Parses the line that starts nextest's final summary (after which the
+/// failed tests are listed again), returning the total time taken.
+///
+/// ```text
+///      Summary [   0.010s] 4 tests run: 3 passed, 1 failed, 1 skipped
+/// ```
+pub(crate) fn parse_summary(line: &str) -> Option<Duration> {
+    let rest = line.trim_start().strip_prefix("Summary [")?;
+    let (duration, _) = rest.split_once(']')?;
+    Some(parse_duration(duration).unwrap_or_default())
+}
+
+/// Parses a duration such as `   0.012s`.
+fn parse_duration(duration: &str) -> Option<Duration> {
+    duration
+        .trim()
+        .strip_suffix('s')
+        .and_then(|secs| secs.parse().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+}
+
+fn parse_status(status: &str) -> Option<Status> {
+    match status {
+        "PASS" | "LEAK" => Some(Status::Pass),
+        "FAIL" | "LEAK-FAIL" | "TIMEOUT" | "ABORT" => Some(Status::Fail),
+        "SKIP" => Some(Status::Ignored),
+        _ if status.starts_with("SIG") => Some(Status::Fail),
+        _ => None,
+    }
+}
+
+/// Removes the `(3/42)` progress counter that newer versions of nextest show
+/// before the binary ID.
+fn strip_progress(rest: &str) -> &str {
+    rest.strip_prefix('(')
+        .and_then(|r| r.split_once(") "))
+        .filter(|(counter, _)| counter.contains('/'))
+        .map_or(rest, |(_, r)| r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::result, TargetKind};
+
+    fn target(name: &str, kind: TargetKind) -> Target {
+        Target {
+            krate: Some("demo".into()),
+            name: name.into(),
+            kind,
+            hash: None,
+        }
+    }
+
+    #[test]
+    fn parse_line_fn_returns_expected_result() {
+        struct Case {
+            line: &'static str,
+            want: Option<TestResult>,
+        }
+        let cases = Vec::from([
+            Case {
+                line: "    Starting 4 tests across 1 binary (1 test skipped)",
+                want: None,
+            },
+            Case {
+                line: "        PASS [   0.004s] demo fancy_module::fancy_function_fn_returns_right_answer",
+                want: Some(TestResult {target: Some(target("demo", TargetKind::Lib)), duration: Some(Duration::from_millis(4)), ..result("fancy_module::fancy_function_fn_returns_right_answer", Some("fancy_module"), "fancy_function returns right answer", Status::Pass)}),
+            },
+            Case {
+                line: "        FAIL [   0.250s] (2/4) demo::cli tests::add_returns_2_for_1_plus_1",
+                want: Some(TestResult {target: Some(target("cli", TargetKind::Test)), duration: Some(Duration::from_millis(250)), ..result("tests::add_returns_2_for_1_plus_1", None, "add returns 2 for 1 plus 1", Status::Fail)}),
+            },
+            Case {
+                line: "        SKIP [         ] demo tests::ignored_test",
+                want: Some(TestResult {target: Some(target("demo", TargetKind::Lib)), ..result("tests::ignored_test", None, "ignored test", Status::Ignored)}),
+            },
+            Case {
+                line: "     SIGSEGV [   0.100s] demo tests::crashes",
+                want: Some(TestResult {target: Some(target("demo", TargetKind::Lib)), duration: Some(Duration::from_millis(100)), ..result("tests::crashes", None, "crashes", Status::Fail)}),
+            },
+            Case {
+                line: "  TRY 1 FAIL [   0.100s] demo tests::flaky",
+                want: Some(TestResult {target: Some(target("demo", TargetKind::Lib)), duration: Some(Duration::from_millis(100)), ..result("tests::flaky", None, "flaky", Status::Fail)}),
+            },
+            Case {
+                line: "   RETRY 2/3 [         ] demo tests::flaky",
+                want: None,
+            },
+            Case {
+                line: "  TRY 2 PASS [   0.100s] demo tests::flaky",
+                want: Some(TestResult {target: Some(target("demo", TargetKind::Lib)), duration: Some(Duration::from_millis(100)), ..result("tests::flaky", None, "flaky", Status::Pass)}),
+            },
+            Case {
+                line: r#"{"type":"test","event":"ok","name":"demo::cli$tests::it_works","exec_time":0.5}"#,
+                want: Some(TestResult {target: Some(target("cli", TargetKind::Test)), duration: Some(Duration::from_millis(500)), ..result("tests::it_works", None, "it works", Status::Pass)}),
+            },
+            Case {
+                line: "     Summary [   0.010s] 4 tests run: 3 passed, 1 failed, 1 skipped",
+                want: None,
+            },
+        ]);
+        for case in cases {
+            assert_eq!(case.want, parse_line(case.line).unwrap());
+        }
+    }
+}
@@ -0,0 +1,734 @@
+//! Stateful parsing of the output of a whole test run.
+
+use std::{
+    collections::{HashMap, HashSet},
+    process::ExitStatus,
+    time::Duration,
+};
+
+use crate::{
+    json,
+    messages::{self, CargoMessage},
+    nextest, parse_line, test_result, Error, Result, Runner, Status, Summary, Target, TestResult,
+};
+
+/// Parses the output of a test run line by line, keeping track of which
+/// test binary ([`Target`]) is currently running, and collecting the output
+/// of any failed tests.
+///
+/// Since `cargo test` only shows the output of failed tests once all the
+/// tests in a binary have finished, failed results are held back until
+/// their output is known.
+///
+/// Once each binary finishes, the results parsed for it are checked against
+/// the counts libtest reports, and any mismatch is recorded as a warning (see
+/// [`Parser::take_warnings`]).
+#[derive(Debug, Default)]
+pub struct Parser {
+    runner: Runner,
+    target: Option<Target>,
+    /// The crate each test binary belongs to, by the hash in its file name,
+    /// for targets whose crate can't be told from their own line of output
+    /// (see [`Runner::spawn`]).
+    crates: HashMap<String, String>,
+    /// Failed tests whose output hasn't been seen yet.
+    failed: Vec<TestResult>,
+    /// The index in `failed` of the test whose output is being collected.
+    capturing: Option<usize>,
+    /// Whether nextest has started printing its final summary, which repeats
+    /// the failed tests.
+    finished: bool,
+    /// The tests nextest has retried, whose failed attempts are held back in
+    /// `failed` until they're superseded by a later attempt, or nextest
+    /// finishes, and the last attempt is known to have failed.
+    retried: HashSet<(Target, String)>,
+    /// The total time taken by the test binaries that have finished.
+    duration: Duration,
+    /// Whether any tests have started running.
+    started: bool,
+    /// The output seen before any tests started running, such as compiler
+    /// diagnostics.
+    build_output: Vec<String>,
+    /// The results parsed for the current test binary so far.
+    seen: Summary,
+    /// Problems noticed that didn't stop parsing.
+    warnings: Vec<Error>,
+    /// Whether the output is a list of tests, rather than their results.
+    listing: bool,
+}
+
+impl Parser {
+    /// Creates a parser for the output of the given runner.
+    #[must_use]
+    pub fn new(runner: Runner) -> Self {
+        Self {
+            runner,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a parser for the output of `cargo test -- --list` (see
+    /// [`Runner::list`]), which names each test without running it. Each
+    /// test is returned with the status [`Status::NotRun`].
+    #[must_use]
+    pub fn list(runner: Runner) -> Self {
+        Self {
+            runner,
+            listing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Parses the next line of output, returning any test results that are
+    /// now complete.
+    ///
+    /// # Errors
+    ///
+    /// If the line looks like a test result, but can't be parsed. Such lines
+    /// are treated as output if they belong to a failed test, though.
+    pub fn parse_line(&mut self, line: impl AsRef<str>) -> Result<Vec<TestResult>> {
+        let line = line.as_ref();
+        if let Some(message) = messages::parse_message(line) {
+            match message {
+                CargoMessage::Executable { hash, krate } => {
+                    self.crates.insert(hash, krate);
+                }
+                CargoMessage::Diagnostic(rendered) if !self.started => {
+                    self.build_output
+                        .extend(rendered.lines().map(ToString::to_string));
+                }
+                CargoMessage::Diagnostic(_) | CargoMessage::Other => {}
+            }
+            return Ok(Vec::new());
+        }
+        let results = match self.runner {
+            Runner::Cargo => self.parse_cargo_line(line),
+            Runner::Nextest => self.parse_nextest_line(line),
+        }?;
+        self.started |= !results.is_empty();
+        if !self.started {
+            self.build_output.push(line.to_string());
+        }
+        Ok(results)
+    }
+
+    /// Checks the exit status of the test command, once it has finished.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::BuildFailed`], with the build output, if the command failed
+    /// before any tests started running, and the output shows that the tests
+    /// couldn't be compiled. [`Error::CommandFailed`] if it failed before any
+    /// tests started running for some other reason, such as an argument that
+    /// cargo doesn't accept.
+    pub fn check_build(&self, status: ExitStatus) -> Result<()> {
+        if status.success() || self.started {
+            return Ok(());
+        }
+        let output = self.build_output.clone();
+        if output.iter().any(|line| is_build_error(line)) {
+            Err(Error::BuildFailed { status, output })
+        } else {
+            Err(Error::CommandFailed { status, output })
+        }
+    }
+
+    /// Reports whether any tests have started running. If the test command
+    /// fails before this happens, it's most likely that the build failed.
+    #[must_use]
+    pub fn tests_started(&self) -> bool {
+        self.started
+    }
+
+    /// Returns the output seen before any tests started running, such as
+    /// cargo's compiler diagnostics.
+    #[must_use]
+    pub fn build_output(&self) -> &[String] {
+        &self.build_output
+    }
+
+    /// Records a problem, such as an error from [`Parser::parse_line`], as a
+    /// warning, to be returned by [`Parser::take_warnings`].
+    pub fn warn(&mut self, warning: Error) {
+        self.warnings.push(warning);
+    }
+
+    /// Returns any problems noticed since this was last called, which didn't
+    /// stop the output from being parsed, but mean that some results may be
+    /// missing. For example, [`Error::CountMismatch`].
+    pub fn take_warnings(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Returns the total time taken to run the tests, as reported by the test
+    /// binaries seen so far.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Returns any results still being held back, once there is no more
+    /// output to parse.
+    pub fn finish(&mut self) -> Vec<TestResult> {
+        self.capturing = None;
+        std::mem::take(&mut self.failed)
+            .into_iter()
+            .map(|mut result| {
+                result.failure = result
+                    .failure
+                    .map(|failure| failure.trim_matches('\n').to_string())
+                    .filter(|failure| !failure.is_empty());
+                result
+            })
+            .collect()
+    }
+
+    /// Returns the failed results being held back, like [`Parser::finish`],
+    /// except for failed attempts at tests that may yet be retried.
+    fn release(&mut self) -> Vec<TestResult> {
+        let retried = |result: &TestResult| {
+            result.target.as_ref().is_some_and(|target| {
+                self.retried
+                    .contains(&(target.clone(), result.path.clone()))
+            })
+        };
+        let (held, failed) = std::mem::take(&mut self.failed)
+            .into_iter()
+            .partition(retried);
+        self.failed = failed;
+        let released = self.finish();
+        self.failed = held;
+        released
+    }
+
+    fn parse_cargo_line(&mut self, line: &str) -> Result<Vec<TestResult>> {
+        if let Some(mut target) = Target::from_cargo_line(line) {
+            let results = self.finish();
+            if target.krate.is_none() {
+                target.krate = target
+                    .hash
+                    .as_ref()
+                    .and_then(|hash| self.crates.get(hash))
+                    .cloned();
+            }
+            self.target = Some(target);
+            self.started = true;
+            self.seen = Summary::default();
+            return Ok(results);
+        }
+        if self.listing {
+            return Ok(line
+                .strip_suffix(": test")
+                .map(|test| TestResult {
+                    target: self.target.clone(),
+                    ..test_result(test, Status::NotRun, None)
+                })
+                .into_iter()
+                .collect());
+        }
+        if let Some(summary) = line.strip_prefix("test result:") {
+            self.check_counts(&parse_summary(summary));
+            return Ok(self.finish());
+        }
+        if json::is_event(line) {
+            if let Some(summary) = json::parse_suite(line) {
+                self.check_counts(&summary);
+                return Ok(Vec::new());
+            }
+        }
+        if let Some(test) = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"))
+        {
+            self.capturing = self.failed.iter().position(|result| result.path == test);
+            return Ok(Vec::new());
+        }
+        if line == "failures:" {
+            self.capturing = None;
+            return Ok(Vec::new());
+        }
+        if let Some(result) = self.or_capture(parse_line(line), line)? {
+            let mut result = TestResult {
+                target: self.target.clone(),
+                ..result
+            };
+            self.seen.add(&result);
+            if result.status == Status::Fail && !json::is_event(line) {
+                result.failure = Some(String::new());
+                self.failed.push(result);
+                return Ok(Vec::new());
+            }
+            return Ok(vec![result]);
+        }
+        Ok(Vec::new())
+    }
+
+    fn parse_nextest_line(&mut self, line: &str) -> Result<Vec<TestResult>> {
+        if self.finished {
+            return Ok(Vec::new());
+        }
+        if let Some(duration) = nextest::parse_summary(line) {
+            self.duration = duration;
+            self.finished = true;
+            return Ok(self.finish());
+        }
+        if line.trim_start().starts_with("Starting ") {
+            self.started = true;
+            return Ok(Vec::new());
+        }
+        if nextest::is_status_line(line) {
+            self.started = true;
+            if let Some(attempt) = nextest::parse_attempt(line) {
+                self.failed.retain(|held| {
+                    held.target.as_ref() != Some(&attempt.0) || held.path != attempt.1
+                });
+                self.retried.insert(attempt);
+            }
+            let mut results = self.release();
+            if let Some(mut result) = nextest::parse_line(line)? {
+                if result.status == Status::Fail && !json::is_event(line) {
+                    result.failure = Some(String::new());
+                    self.failed.push(result);
+                } else {
+                    results.push(result);
+                }
+            }
+            return Ok(results);
+        }
+        if nextest::is_output_header(line) {
+            self.capturing = self.failed.len().checked_sub(1);
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .or_capture(nextest::parse_line(line), line)?
+            .into_iter()
+            .collect())
+    }
+
+    /// Returns the result of parsing a line, unless it wasn't a test result
+    /// (or couldn't be parsed as one) while the output of a failed test is
+    /// being captured, in which case the line is captured instead.
+    fn or_capture(
+        &mut self,
+        parsed: Result<Option<TestResult>>,
+        line: &str,
+    ) -> Result<Option<TestResult>> {
+        match parsed {
+            Err(_) | Ok(None) if self.capturing.is_some() => {
+                self.capture(line);
+                Ok(None)
+            }
+            parsed => parsed,
+        }
+    }
+
+    /// Adds the time taken by a test binary that has finished to the total,
+    /// and checks libtest's counts of its results against those parsed.
+    fn check_counts(&mut self, reported: &Summary) {
+        self.duration += reported.duration;
+        let parsed = std::mem::take(&mut self.seen);
+        if (reported.passed, reported.failed, reported.ignored)
+            != (parsed.passed, parsed.failed, parsed.ignored)
+        {
+            self.warnings.push(Error::CountMismatch {
+                target: self
+                    .target
+                    .as_ref()
+                    .map_or_else(|| "tests".to_string(), ToString::to_string),
+                reported: reported.counts(),
+                parsed: parsed.counts(),
+            });
+        }
+    }
+
+    /// Adds a line to the output of the failed test being captured, if any.
+    fn capture(&mut self, line: &str) {
+        if let Some(result) = self.capturing.and_then(|index| self.failed.get_mut(index)) {
+            let failure = result.failure.get_or_insert_default();
+            failure.push_str(line);
+            failure.push('\n');
+        }
+    }
+}
+
+/// Parses the counts and time taken from a libtest summary, such as
+/// `ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.02s`.
+fn parse_summary(summary: &str) -> Summary {
+    let mut parsed = Summary::default();
+    for part in summary.split(';') {
+        let part = part.trim();
+        if let Some(secs) = part.strip_prefix("finished in ") {
+            parsed.duration = secs
+                .strip_suffix('s')
+                .and_then(|secs| secs.parse().ok())
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .unwrap_or_default();
+            continue;
+        }
+        // The first part starts with the outcome, such as `ok. `.
+        let part = part.rsplit_once(". ").map_or(part, |(_, part)| part);
+        let Some((count, what)) = part.split_once(' ') else {
+            continue;
+        };
+        let Ok(count) = count.parse() else {
+            continue;
+        };
+        match what {
+            "passed" => parsed.passed = count,
+            "failed" => parsed.failed = count,
+            "ignored" => parsed.ignored = count,
+            _ => {}
+        }
+    }
+    parsed
+}
+
+/// Reports whether a line of cargo's output says that something couldn't be
+/// built.
+fn is_build_error(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("error: could not compile")
+        || line.starts_with("error: failed to run custom build command")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(runner: Runner, output: &str) -> Vec<TestResult> {
+        let mut parser = Parser::new(runner);
+        let mut results = Vec::new();
+        for line in output.lines() {
+            results.extend(parser.parse_line(line).unwrap());
+        }
+        results.extend(parser.finish());
+        results
+    }
+
+    #[test]
+    fn parser_attaches_failure_output_to_failed_tests() {
+        let results = parse(
+            Runner::Cargo,
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)
+
+running 3 tests
+test tests::fails_first ... FAILED
+test tests::passes ... ok
+test tests::fails_second ... FAILED
+
+failures:
+
+---- tests::fails_first stdout ----
+
+thread 'tests::fails_first' panicked at src/lib.rs:14:9:
+first
+
+---- tests::fails_second stdout ----
+second
+
+
+failures:
+    tests::fails_first
+    tests::fails_second
+
+test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.02s
+",
+        );
+        let summary: Vec<_> = results
+            .iter()
+            .map(|result| (result.path.as_str(), result.failure.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("tests::passes", None),
+                (
+                    "tests::fails_first",
+                    Some("thread 'tests::fails_first' panicked at src/lib.rs:14:9:\nfirst")
+                ),
+                ("tests::fails_second", Some("second")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parser_tells_apart_targets_with_the_same_name_in_different_crates() {
+        let results = parse(
+            Runner::Cargo,
+            r#"   Compiling bar-baz v0.1.0 (/tmp/ws/bar-baz)
+   Compiling foo v0.1.0 (/tmp/ws/foo)
+{"reason":"compiler-artifact","package_id":"path+file:///tmp/ws/foo#0.1.0","manifest_path":"/tmp/ws/foo/Cargo.toml","target":{"kind":["test"],"crate_types":["bin"],"name":"cli","src_path":"/tmp/ws/foo/tests/cli.rs","edition":"2021","doc":false,"doctest":false,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/tmp/ws/target/debug/deps/cli-b649f6ea8b966137"],"executable":"/tmp/ws/target/debug/deps/cli-b649f6ea8b966137","fresh":false}
+{"reason":"compiler-artifact","package_id":"path+file:///tmp/ws/bar-baz#0.1.0","manifest_path":"/tmp/ws/bar-baz/Cargo.toml","target":{"kind":["test"],"crate_types":["bin"],"name":"cli","src_path":"/tmp/ws/bar-baz/tests/cli.rs","edition":"2021","doc":false,"doctest":false,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/tmp/ws/target/debug/deps/cli-19da1b0f0ec8cbd3"],"executable":"/tmp/ws/target/debug/deps/cli-19da1b0f0ec8cbd3","fresh":false}
+{"reason":"build-finished","success":true}
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.39s
+     Running tests/cli.rs (target/debug/deps/cli-19da1b0f0ec8cbd3)
+
+running 1 test
+test cli_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+     Running tests/cli.rs (target/debug/deps/cli-b649f6ea8b966137)
+
+running 1 test
+test cli_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"#,
+        );
+        let targets: Vec<_> = results
+            .iter()
+            .map(|result| result.target.as_ref().unwrap().to_string())
+            .collect();
+        assert_eq!(targets, ["bar_baz::cli (test)", "foo::cli (test)"]);
+    }
+
+    #[test]
+    fn parser_never_mistakes_different_binaries_with_the_same_name() {
+        let results = parse(
+            Runner::Cargo,
+            "     Running tests/cli.rs (target/debug/deps/cli-19da1b0f0ec8cbd3)
+test cli_works ... ok
+     Running tests/cli.rs (target/debug/deps/cli-b649f6ea8b966137)
+test cli_works ... ok
+",
+        );
+        assert_eq!(results.len(), 2);
+        assert_ne!(results[0].target, results[1].target);
+    }
+
+    #[test]
+    fn parser_ignores_test_output_that_looks_like_json() {
+        let mut parser = Parser::default();
+        let mut results = Vec::new();
+        for line in [
+            "test tests::prints ... ok",
+            "successes:",
+            "---- tests::prints stdout ----",
+            "{ debug: 1 }",
+            "test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s",
+        ] {
+            results.extend(parser.parse_line(line).unwrap());
+        }
+        assert_eq!(results.len(), 1);
+        assert!(parser.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn parser_captures_unparseable_lines_in_failure_output() {
+        let mut parser = Parser::default();
+        for line in [
+            "test tests::fails ... FAILED",
+            "---- tests::fails stdout ----",
+            "{ not json",
+            "test tests::inner ... exploded",
+        ] {
+            assert_eq!(parser.parse_line(line).unwrap(), []);
+        }
+        parser.parse_line("failures:").unwrap();
+        assert!(matches!(
+            parser.parse_line("test tests::outer ... exploded"),
+            Err(Error::UnknownStatus(_))
+        ));
+        let results = parser.finish();
+        assert_eq!(
+            results[0].failure.as_deref(),
+            Some("{ not json\ntest tests::inner ... exploded")
+        );
+    }
+
+    #[test]
+    fn parser_reports_each_nextest_failure_once_with_its_output() {
+        let results = parse(
+            Runner::Nextest,
+            "    Starting 2 tests across 1 binary
+        FAIL [   0.005s] demo tests::fails
+──── STDOUT:             demo tests::fails
+
+running 1 test
+──── STDERR:             demo tests::fails
+thread 'tests::fails' panicked at src/lib.rs:14:9
+
+        PASS [   0.004s] demo tests::passes
+────────────
+     Summary [   0.010s] 2 tests run: 1 passed, 1 failed, 0 skipped
+        FAIL [   0.005s] demo tests::fails
+error: test run failed
+",
+        );
+        let summary: Vec<_> = results
+            .iter()
+            .map(|result| (result.path.as_str(), result.failure.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "tests::fails",
+                    Some("running 1 test\nthread 'tests::fails' panicked at src/lib.rs:14:9")
+                ),
+                ("tests::passes", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn parser_reports_only_the_last_attempt_at_a_retried_nextest_test() {
+        let results = parse(
+            Runner::Nextest,
+            "    Starting 3 tests across 1 binary
+  TRY 1 FAIL [   0.005s] demo tests::flaky
+  TRY 1 FAIL [   0.005s] demo tests::broken
+──── STDERR:             demo tests::broken
+first failure
+   RETRY 2/2 [         ] demo tests::flaky
+        PASS [   0.004s] demo tests::passes
+   RETRY 2/2 [         ] demo tests::broken
+  TRY 2 PASS [   0.005s] demo tests::flaky
+  TRY 2 FAIL [   0.005s] demo tests::broken
+──── STDERR:             demo tests::broken
+second failure
+     Summary [   0.020s] 3 tests run: 2 passed (1 flaky), 1 failed, 0 skipped
+        FAIL [   0.005s] demo tests::broken
+",
+        );
+        let summary: Vec<_> = results
+            .iter()
+            .map(|result| {
+                (
+                    result.path.as_str(),
+                    &result.status,
+                    result.failure.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("tests::passes", &Status::Pass, None),
+                ("tests::flaky", &Status::Pass, None),
+                ("tests::broken", &Status::Fail, Some("second failure")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parser_keeps_build_output_until_tests_start() {
+        let mut parser = Parser::default();
+        for line in [
+            "   Compiling demo v0.1.0 (/home/user/demo)",
+            "error[E0308]: mismatched types",
+            r#"{"reason":"compiler-message","package_id":"path+file:///home/user/demo#0.1.0","manifest_path":"/home/user/demo/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"demo","src_path":"/home/user/demo/src/lib.rs","edition":"2024","doc":true,"doctest":true,"test":true},"message":{"rendered":"For more information about this error, try `rustc --explain E0308`.\n","$message_type":"diagnostic","children":[],"level":"failure-note","message":"For more information about this error, try `rustc --explain E0308`.","spans":[],"code":null}}"#,
+            "error: could not compile `demo` (lib test) due to 1 previous error",
+            r#"{"reason":"build-finished","success":false}"#,
+        ] {
+            parser.parse_line(line).unwrap();
+        }
+        assert!(!parser.tests_started());
+        assert_eq!(
+            parser.build_output(),
+            [
+                "   Compiling demo v0.1.0 (/home/user/demo)",
+                "error[E0308]: mismatched types",
+                "For more information about this error, try `rustc --explain E0308`.",
+                "error: could not compile `demo` (lib test) due to 1 previous error",
+            ]
+        );
+        parser
+            .parse_line(
+                "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)",
+            )
+            .unwrap();
+        parser.parse_line("running 0 tests").unwrap();
+        assert!(parser.tests_started());
+        assert_eq!(parser.build_output().len(), 4);
+    }
+
+    #[test]
+    fn parser_warns_when_results_are_missing_from_a_test_binary() {
+        let mut parser = Parser::default();
+        for line in [
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)",
+            "test tests::passes ... ok",
+            "test tests::is_ignored ... ignored",
+            "test result: ok. 1 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s",
+        ] {
+            parser.parse_line(line).unwrap();
+        }
+        assert!(parser.take_warnings().is_empty());
+        for line in [
+            "     Running tests/cli.rs (target/debug/deps/cli-0123456789abcdef)",
+            "test tests::passes ... ok",
+            "test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s",
+        ] {
+            parser.parse_line(line).unwrap();
+        }
+        let warnings: Vec<_> = parser
+            .take_warnings()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            warnings,
+            ["cli (test): libtest reported 2 passed, 0 failed, 0 ignored, but testdox found 1 passed, 0 failed, 0 ignored"]
+        );
+    }
+
+    #[test]
+    fn parser_lists_tests_without_results() {
+        let mut parser = Parser::list(Runner::Cargo);
+        let mut results = Vec::new();
+        for line in [
+            "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)",
+            "maths::tests::adds_numbers: test",
+            "",
+            "1 tests, 0 benchmarks",
+            "   Doc-tests demo",
+            "src/lib.rs - add (line 3): test",
+        ] {
+            results.extend(parser.parse_line(line).unwrap());
+        }
+        let listed: Vec<_> = results
+            .iter()
+            .map(|result| {
+                (
+                    result.target.as_ref().map(ToString::to_string),
+                    result.module.as_deref(),
+                    result.name.as_str(),
+                    &result.status,
+                )
+            })
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                (
+                    Some("demo (lib)".to_string()),
+                    Some("maths"),
+                    "adds numbers",
+                    &Status::NotRun
+                ),
+                (
+                    Some("demo (doctests)".to_string()),
+                    Some("add"),
+                    "example (src/lib.rs:3)",
+                    &Status::NotRun
+                ),
+            ]
+        );
+        assert!(parser.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn parser_adds_up_time_taken_by_each_test_binary() {
+        let mut parser = Parser::default();
+        for line in [
+            "test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.25s",
+            r#"{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.5 }"#,
+            "test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 1.00s",
+        ] {
+            parser.parse_line(line).unwrap();
+        }
+        assert_eq!(parser.duration(), Duration::from_millis(1750));
+    }
+}
@@ -0,0 +1,197 @@
+//! Running tests with `cargo test` or `cargo nextest`.
+
+use std::{
+    io::{self, BufRead, BufReader, PipeReader},
+    process::{Child, Command, ExitStatus},
+    str::FromStr,
+};
+
+use crate::{Error, Result};
+
+/// Extra libtest arguments that make it report results as JSON events.
+const LIBTEST_JSON_ARGS: [&str; 5] = [
+    "-Z",
+    "unstable-options",
+    "--format",
+    "json",
+    "--report-time",
+];
+
+/// The tool used to run the tests.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Runner {
+    /// `cargo test`.
+    #[default]
+    Cargo,
+    /// `cargo nextest run` (see <https://nexte.st>).
+    Nextest,
+}
+
+impl Runner {
+    /// Starts running tests, with any supplied extra arguments.
+    ///
+    /// If `json` is true, and the runner can produce them, results are
+    /// requested as libtest-style JSON events. Otherwise, the runner's usual
+    /// human-readable output is used.
+    ///
+    /// With `cargo test`, cargo's own messages are requested as JSON (unless
+    /// `extra_args` choose a message format), so that the crate each test
+    /// binary belongs to is known.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::CargoNotFound`] if there's no `cargo` command, or
+    /// [`Error::Spawn`] if the test command couldn't be started for some
+    /// other reason.
+    pub fn spawn(self, extra_args: Vec<String>, json: bool) -> Result<TestRun> {
+        let mut cmd = Command::new("cargo");
+        match self {
+            Runner::Cargo => {
+                cmd.arg("test");
+                let extra_args = with_message_format(extra_args);
+                if json && libtest_json_supported() {
+                    cmd.args(with_libtest_args(extra_args, &LIBTEST_JSON_ARGS));
+                } else {
+                    cmd.args(extra_args);
+                }
+            }
+            Runner::Nextest => {
+                cmd.args(["nextest", "run"]);
+                if json {
+                    cmd.args(["--message-format", "libtest-json"]);
+                    cmd.env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+                }
+                cmd.args(extra_args);
+            }
+        }
+        TestRun::spawn(cmd)
+    }
+
+    /// Starts listing the tests, with any supplied extra arguments, without
+    /// running them (see [`Parser::list`](crate::Parser::list)).
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnsupportedRunner`] if the runner can't list tests in a form
+    /// that can be parsed, or as for [`Runner::spawn`].
+    pub fn list(self, extra_args: Vec<String>) -> Result<TestRun> {
+        match self {
+            Runner::Cargo => {
+                let mut cmd = Command::new("cargo");
+                cmd.arg("test").args(with_libtest_args(
+                    with_message_format(extra_args),
+                    &["--list"],
+                ));
+                TestRun::spawn(cmd)
+            }
+            Runner::Nextest => Err(Error::UnsupportedRunner("listing tests")),
+        }
+    }
+}
+
+impl FromStr for Runner {
+    type Err = Error;
+
+    fn from_str(runner: &str) -> Result<Self, Self::Err> {
+        match runner {
+            "cargo" => Ok(Runner::Cargo),
+            "nextest" => Ok(Runner::Nextest),
+            _ => Err(Error::UnknownRunner(runner.to_string())),
+        }
+    }
+}
+
+/// A running test process.
+///
+/// Iterating over a `TestRun` yields each line of the command's output
+/// (standard output and standard error combined) as soon as it is produced,
+/// so that results can be shown while the tests are still running, or an
+/// error if the output couldn't be read.
+pub struct TestRun {
+    child: Child,
+    output: BufReader<PipeReader>,
+}
+
+impl TestRun {
+    fn spawn(mut cmd: Command) -> Result<Self> {
+        let (reader, writer) = io::pipe()?;
+        cmd.stdout(writer.try_clone()?).stderr(writer);
+        let child = cmd.spawn().map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => Error::CargoNotFound,
+            _ => Error::Spawn {
+                command: format!("{cmd:?}"),
+                source,
+            },
+        })?;
+        // The command holds the write end of the pipe, which must be closed
+        // before reading, or we'll never see end of file.
+        drop(cmd);
+        Ok(Self {
+            child,
+            output: BufReader::new(reader),
+        })
+    }
+
+    /// Waits for the test process to exit, and returns its exit status.
+    ///
+    /// # Errors
+    ///
+    /// If waiting for the process fails.
+    pub fn wait(mut self) -> Result<ExitStatus> {
+        Ok(self.child.wait()?)
+    }
+}
+
+impl Iterator for TestRun {
+    type Item = Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buf = Vec::new();
+        match self.output.read_until(b'\n', &mut buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                Some(Ok(line.trim_end_matches(['\r', '\n']).to_string()))
+            }
+            Err(err) => Some(Err(err.into())),
+        }
+    }
+}
+
+/// Reports whether the active toolchain's libtest can produce JSON events.
+///
+/// This is only possible with nightly (or locally built) toolchains; on
+/// stable, `cargo test` output must be parsed in its human-readable form.
+#[must_use]
+pub fn libtest_json_supported() -> bool {
+    Command::new("cargo")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        })
+}
+
+/// Adds `--message-format json` to a list of `cargo test` arguments (before
+/// the `--` separator, if any), unless they already choose a message format.
+fn with_message_format(mut args: Vec<String>) -> Vec<String> {
+    let cargo_args = args.iter().take_while(|arg| *arg != "--");
+    if !cargo_args
+        .into_iter()
+        .any(|arg| arg.starts_with("--message-format"))
+    {
+        args.splice(0..0, ["--message-format".into(), "json".into()]);
+    }
+    args
+}
+
+/// Adds `libtest_args` to a list of `cargo test` arguments, after the `--`
+/// separator (which is added if necessary).
+fn with_libtest_args(mut args: Vec<String>, libtest_args: &[&str]) -> Vec<String> {
+    if !args.iter().any(|arg| arg == "--") {
+        args.push("--".into());
+    }
+    args.extend(libtest_args.iter().map(ToString::to_string));
+    args
+}
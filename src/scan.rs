@@ -0,0 +1,655 @@
+//! Finding tests by parsing the source code, without compiling it.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::{TokenStream, TokenTree};
+use syn::{Attribute, Expr, ExprLit, Ident, Item, ItemFn, ItemMod, Lit, Meta, Visibility};
+
+use crate::{test_result, Error, Result, Status, Target, TargetKind, TestResult};
+
+/// Finds the tests in the package whose root directory is `root` (or, if
+/// it's the root of a workspace, in each of its members), by parsing the
+/// source files of its library, binaries, and integration tests, rather than
+/// by building and running them.
+///
+/// Modules declared with `mod foo;` are followed to their files, so that
+/// each test has the same path as `cargo test` would give it. Functions with
+/// a `#[test]` attribute count as tests, as do those with attributes such as
+/// `#[tokio::test]` or `#[rstest]`.
+///
+/// Since the tests aren't run, each has the status [`Status::NotRun`], or
+/// [`Status::Ignored`] if it has an `#[ignore]` attribute. Its
+/// [`location`](TestResult::location) is the file and line where it's
+/// defined, relative to `root`, such as `src/lib.rs:12`.
+///
+/// Also returns a warning ([`NotATest`]) for each function in a
+/// `#[cfg(test)]` module that looks like a test, but has no test attribute,
+/// and is never called, so never runs.
+///
+/// # Errors
+///
+/// If a source file can't be read, or [`Error::Syntax`] if it can't be
+/// parsed.
+pub fn scan(root: &Path) -> Result<(Vec<TestResult>, Vec<NotATest>)> {
+    let mut results = Vec::new();
+    let mut warnings = Vec::new();
+    for package in packages(root)? {
+        for (target, file) in targets(&package)? {
+            let mut scan = Scan {
+                root,
+                target,
+                results: Vec::new(),
+                untested: Vec::new(),
+                idents: HashMap::new(),
+            };
+            let dir = file.parent().unwrap_or(root).to_path_buf();
+            scan.file(&file, &mut Vec::new(), &dir, false)?;
+            results.extend(scan.results);
+            // The definition itself is the only mention of a function that's
+            // never called.
+            warnings.extend(
+                scan.untested
+                    .into_iter()
+                    .filter(|warning| scan.idents.get(&warning.function).copied() == Some(1)),
+            );
+        }
+    }
+    Ok((results, warnings))
+}
+
+/// A warning that a function in a `#[cfg(test)]` module looks like a test,
+/// but has no test attribute, and is never called, so it never runs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotATest {
+    /// The name of the function.
+    pub function: String,
+    /// Where the function is defined, such as `src/lib.rs:12`.
+    pub location: String,
+}
+
+impl Display for NotATest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} is not a test: it has no #[test] attribute, and is never called",
+            self.location, self.function
+        )
+    }
+}
+
+/// Returns the directories of the packages to scan: just `root`, unless it's
+/// the root of a workspace, in which case its members (and `root` itself, if
+/// it's a package too). Members such as `crates/*` are found by listing the
+/// directories that have a `Cargo.toml`.
+fn packages(root: &Path) -> Result<Vec<PathBuf>> {
+    let manifest = fs::read_to_string(root.join("Cargo.toml")).unwrap_or_default();
+    let Some(members) = workspace_members(&manifest) else {
+        return Ok(vec![root.to_path_buf()]);
+    };
+    let mut packages = Vec::new();
+    if package_name(&manifest).is_some() {
+        packages.push(root.to_path_buf());
+    }
+    for member in members {
+        let Some(dir) = member.strip_suffix("/*") else {
+            packages.push(root.join(member));
+            continue;
+        };
+        let mut found = Vec::new();
+        for entry in fs::read_dir(root.join(dir))? {
+            let path = entry?.path();
+            if path.join("Cargo.toml").is_file() {
+                found.push(path);
+            }
+        }
+        found.sort();
+        packages.extend(found);
+    }
+    Ok(packages)
+}
+
+/// Returns the test binaries that `cargo test` would build for the package
+/// in `root`, found by cargo's usual conventions, with the file at the root
+/// of each one's module tree.
+fn targets(root: &Path) -> Result<Vec<(Target, PathBuf)>> {
+    let package = fs::read_to_string(root.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| package_name(&manifest))
+        .or_else(|| {
+            let root = root.canonicalize().ok()?;
+            Some(root.file_name()?.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default()
+        .replace('-', "_");
+    let mut targets = Vec::new();
+    for (file, kind) in [("lib.rs", TargetKind::Lib), ("main.rs", TargetKind::Bin)] {
+        let file = root.join("src").join(file);
+        if file.is_file() {
+            let target = Target {
+                krate: Some(package.clone()),
+                name: package.clone(),
+                kind,
+                hash: None,
+            };
+            targets.push((target, file));
+        }
+    }
+    for (dir, kind) in [("src/bin", TargetKind::Bin), ("tests", TargetKind::Test)] {
+        let Ok(entries) = fs::read_dir(root.join(dir)) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let file = if path.is_dir() {
+                path.join("main.rs")
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                path.clone()
+            } else {
+                continue;
+            };
+            if let (true, Some(stem)) = (file.is_file(), path.file_stem()) {
+                let target = Target {
+                    krate: Some(package.clone()),
+                    name: stem.to_string_lossy().replace('-', "_"),
+                    kind,
+                    hash: None,
+                };
+                found.push((target, file));
+            }
+        }
+        found.sort();
+        targets.extend(found);
+    }
+    Ok(targets)
+}
+
+/// Finds the package name in a `Cargo.toml` manifest.
+fn package_name(manifest: &str) -> Option<String> {
+    let mut in_package = false;
+    for line in manifest.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_package = line == "[package]";
+        } else if let (true, Some(("name", value))) = (
+            in_package,
+            line.split_once('=').map(|(k, v)| (k.trim(), v.trim())),
+        ) {
+            return Some(value.trim_matches('"').to_string());
+        }
+    }
+    None
+}
+
+/// Finds the `members` of the `[workspace]` section in a `Cargo.toml`
+/// manifest, if it has one.
+fn workspace_members(manifest: &str) -> Option<Vec<String>> {
+    let mut workspace = false;
+    let mut in_workspace = false;
+    let mut lines = manifest.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line.starts_with('[') {
+            in_workspace = line == "[workspace]";
+            workspace |= in_workspace;
+        } else if let (true, Some(("members", value))) = (
+            in_workspace,
+            line.split_once('=').map(|(k, v)| (k.trim(), v.trim())),
+        ) {
+            // The array may be spread over several lines.
+            let mut members = value.to_string();
+            while !members.contains(']') {
+                let Some(line) = lines.next() else {
+                    break;
+                };
+                members.push_str(line);
+            }
+            let members = members.replace('\'', "\"");
+            return Some(
+                members
+                    .split('"')
+                    .skip(1)
+                    .step_by(2)
+                    .map(Into::into)
+                    .collect(),
+            );
+        }
+    }
+    workspace.then(Vec::new)
+}
+
+/// The state of a scan of one test binary's source files.
+struct Scan<'a> {
+    root: &'a Path,
+    target: Target,
+    results: Vec<TestResult>,
+    /// Functions in `#[cfg(test)]` modules that look like tests, but aren't,
+    /// with their locations.
+    untested: Vec<NotATest>,
+    /// The number of times each identifier appears in the source files.
+    idents: HashMap<String, usize>,
+}
+
+impl Scan<'_> {
+    /// Scans a source file containing the module `module`, whose child
+    /// modules' files are in `dir`. `cfg_test` says whether the module is
+    /// only compiled for tests.
+    fn file(
+        &mut self,
+        file: &Path,
+        module: &mut Vec<String>,
+        dir: &Path,
+        cfg_test: bool,
+    ) -> Result<()> {
+        let source = fs::read_to_string(file)?;
+        let syntax = syn::parse_file(&source).map_err(|source| Error::Syntax {
+            file: file.to_path_buf(),
+            source,
+        })?;
+        // Functions can be called from within macros, such as `assert!`, so
+        // look at every token, not just the syntax tree.
+        if let Ok(tokens) = source.parse() {
+            self.count_idents(tokens);
+        }
+        self.items(&syntax.items, file, module, dir, cfg_test)
+    }
+
+    fn count_idents(&mut self, tokens: TokenStream) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) => *self.idents.entry(ident.to_string()).or_default() += 1,
+                TokenTree::Group(group) => self.count_idents(group.stream()),
+                TokenTree::Punct(_) | TokenTree::Literal(_) => {}
+            }
+        }
+    }
+
+    fn items(
+        &mut self,
+        items: &[Item],
+        file: &Path,
+        module: &mut Vec<String>,
+        dir: &Path,
+        cfg_test: bool,
+    ) -> Result<()> {
+        for item in items {
+            match item {
+                Item::Fn(function) => self.function(function, file, module, cfg_test),
+                Item::Mod(item) => {
+                    module.push(item.ident.to_string());
+                    let cfg_test = cfg_test || item.attrs.iter().any(is_cfg_test);
+                    let scanned = self.module(item, file, module, dir, cfg_test);
+                    module.pop();
+                    scanned?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Scans a module, either inline or in a file of its own.
+    fn module(
+        &mut self,
+        item: &ItemMod,
+        file: &Path,
+        module: &mut Vec<String>,
+        dir: &Path,
+        cfg_test: bool,
+    ) -> Result<()> {
+        let name = item.ident.to_string();
+        let path = string_attribute(&item.attrs, "path");
+        if let Some((_, items)) = &item.content {
+            let dir = dir.join(path.unwrap_or(name));
+            return self.items(items, file, module, &dir, cfg_test);
+        }
+        if let Some(path) = path {
+            let file = file.parent().unwrap_or(self.root).join(path);
+            let dir = file.parent().unwrap_or(self.root).to_path_buf();
+            return self.file(&file, module, &dir, cfg_test);
+        }
+        let dir = dir.join(&name);
+        for file in [dir.with_extension("rs"), dir.join("mod.rs")] {
+            if file.is_file() {
+                return self.file(&file, module, &dir, cfg_test);
+            }
+        }
+        // The module may be generated by a build script, or only exist on
+        // some platforms.
+        Ok(())
+    }
+
+    fn function(&mut self, function: &ItemFn, file: &Path, module: &[String], cfg_test: bool) {
+        let ident = &function.sig.ident;
+        if !function.attrs.iter().any(is_test_attribute) {
+            if cfg_test && looks_like_test(function) {
+                self.untested.push(NotATest {
+                    function: ident.to_string(),
+                    location: self.location(file, ident),
+                });
+            }
+            return;
+        }
+        let ignored = function
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("ignore"));
+        let status = match ignored {
+            Some(_) => Status::Ignored,
+            None => Status::NotRun,
+        };
+        let path = module
+            .iter()
+            .map(String::as_str)
+            .chain([ident.to_string().as_str()])
+            .collect::<Vec<_>>()
+            .join("::");
+        let mut result = test_result(&path, status, None);
+        result.location = Some(self.location(file, ident));
+        result.target = Some(self.target.clone());
+        result.ignore_reason = ignored.and_then(|attr| string_value(&attr.meta));
+        self.results.push(result);
+    }
+
+    /// Returns the file and line where `ident` is, such as `src/lib.rs:12`.
+    fn location(&self, file: &Path, ident: &Ident) -> String {
+        let file = file.strip_prefix(self.root).unwrap_or(file);
+        let file = file.to_string_lossy().replace('\\', "/");
+        format!("{file}:{}", ident.span().start().line)
+    }
+}
+
+/// Reports whether an attribute makes a function a test, such as `#[test]`,
+/// `#[tokio::test]`, or `#[rstest]`.
+fn is_test_attribute(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "test" || segment.ident == "rstest")
+}
+
+/// Reports whether an attribute is `#[cfg(test)]`.
+fn is_cfg_test(attr: &Attribute) -> bool {
+    attr.path().is_ident("cfg")
+        && attr
+            .parse_args::<Ident>()
+            .is_ok_and(|predicate| predicate == "test")
+}
+
+/// Reports whether a function that isn't a test looks as though it was meant
+/// to be one: it takes no arguments, its name reads like a sentence, such as
+/// `parser_handles_empty_input`, it isn't visible outside its module (as a
+/// helper for other tests might be), and it has no attributes other than
+/// those a test might have, such as `#[ignore]`.
+fn looks_like_test(function: &ItemFn) -> bool {
+    let name = function.sig.ident.to_string();
+    let words = name.split('_').filter(|word| !word.is_empty()).count();
+    function.sig.inputs.is_empty()
+        && matches!(function.vis, Visibility::Inherited)
+        && (words >= 3 || name.contains("_fn_"))
+        && function.attrs.iter().all(|attr| {
+            ["doc", "ignore", "should_panic", "allow", "expect"]
+                .iter()
+                .any(|name| attr.path().is_ident(name))
+        })
+}
+
+/// Returns the value of an attribute such as `#[path = "foo.rs"]`, if any.
+fn string_attribute(attrs: &[Attribute], name: &str) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(name))
+        .find_map(|attr| string_value(&attr.meta))
+}
+
+fn string_value(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(meta) => match &meta.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(value),
+                ..
+            }) => Some(value.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scans a package made of the given files, written to a temporary
+    /// directory.
+    fn scan_package(name: &str, files: &[(&str, &str)]) -> (Vec<TestResult>, Vec<NotATest>) {
+        let root = std::env::temp_dir().join(format!("testdox-{name}-{}", std::process::id()));
+        for (file, source) in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        let scanned = scan(&root);
+        fs::remove_dir_all(&root).unwrap();
+        scanned.unwrap()
+    }
+
+    #[test]
+    fn scan_fn_finds_tests_in_each_module_and_target() {
+        let (results, _) = scan_package(
+            "scan",
+            &[
+                ("Cargo.toml", "[package]\nname = \"my-crate\"\n"),
+                ("src/lib.rs", "mod parser;\n\n#[test]\nfn it_works() {}\n"),
+                (
+                    "src/parser.rs",
+                    "mod nested;
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = \"too slow\"]
+    fn parses_everything() {}
+
+    fn helper() {}
+}
+",
+                ),
+                (
+                    "src/parser/nested.rs",
+                    "#[tokio::test]\nasync fn waits() {}\n",
+                ),
+                ("tests/cli.rs", "#[rstest]\nfn runs_fn_help() {}\n"),
+            ],
+        );
+        let found: Vec<_> = results
+            .iter()
+            .map(|result| {
+                (
+                    result.target.as_ref().unwrap().to_string(),
+                    result.path.as_str(),
+                    result.module.as_deref(),
+                    result.name.as_str(),
+                    result.location.as_deref(),
+                    &result.status,
+                    result.ignore_reason.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "my_crate (lib)".to_string(),
+                    "parser::nested::waits",
+                    Some("parser::nested"),
+                    "waits",
+                    Some("src/parser/nested.rs:2"),
+                    &Status::NotRun,
+                    None,
+                ),
+                (
+                    "my_crate (lib)".to_string(),
+                    "parser::tests::parses_everything",
+                    Some("parser"),
+                    "parses everything",
+                    Some("src/parser.rs:7"),
+                    &Status::Ignored,
+                    Some("too slow"),
+                ),
+                (
+                    "my_crate (lib)".to_string(),
+                    "it_works",
+                    None,
+                    "it works",
+                    Some("src/lib.rs:4"),
+                    &Status::NotRun,
+                    None,
+                ),
+                (
+                    "my_crate::cli (test)".to_string(),
+                    "runs_fn_help",
+                    None,
+                    "runs help",
+                    Some("tests/cli.rs:2"),
+                    &Status::NotRun,
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_name_fn_reads_the_name_from_the_package_section() {
+        for (manifest, want) in [
+            ("[package]\nname = \"foo\"\n", Some("foo")),
+            (
+                "[lib]\nname = \"bar\"\n\n[package]\nname = \"foo\"\n",
+                Some("foo"),
+            ),
+            ("[workspace]\nmembers = []\n", None),
+        ] {
+            assert_eq!(package_name(manifest).as_deref(), want, "{manifest}");
+        }
+    }
+
+    #[test]
+    fn scan_fn_finds_the_tests_of_each_workspace_member() {
+        let (results, _) = scan_package(
+            "workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\n    \"app\",\n    \"crates/*\",\n]\n",
+                ),
+                ("app/Cargo.toml", "[package]\nname = \"app\"\n"),
+                ("app/src/main.rs", "#[test]\nfn it_runs() {}\n"),
+                ("crates/foo/Cargo.toml", "[package]\nname = \"foo\"\n"),
+                ("crates/foo/tests/cli.rs", "#[test]\nfn cli_works() {}\n"),
+                (
+                    "crates/bar-baz/Cargo.toml",
+                    "[package]\nname = \"bar-baz\"\n",
+                ),
+                (
+                    "crates/bar-baz/tests/cli.rs",
+                    "#[test]\nfn cli_works() {}\n",
+                ),
+            ],
+        );
+        let found: Vec<_> = results
+            .iter()
+            .map(|result| {
+                (
+                    result.target.as_ref().unwrap().to_string(),
+                    result.location.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("app (bin)".to_string(), "app/src/main.rs:2"),
+                (
+                    "bar_baz::cli (test)".to_string(),
+                    "crates/bar-baz/tests/cli.rs:2"
+                ),
+                ("foo::cli (test)".to_string(), "crates/foo/tests/cli.rs:2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_members_fn_reads_the_members_of_the_workspace_section() {
+        for (manifest, want) in [
+            (
+                "[workspace]\nmembers = [\"foo\", 'bar-baz']\n",
+                Some(vec!["foo", "bar-baz"]),
+            ),
+            (
+                "[package]\nname = \"foo\"\n\n[workspace]\nmembers = [\n  \"crates/*\",\n]\n",
+                Some(vec!["crates/*"]),
+            ),
+            ("[workspace]\nresolver = \"3\"\n", Some(vec![])),
+            ("[package]\nname = \"foo\"\n", None),
+        ] {
+            assert_eq!(
+                workspace_members(manifest),
+                want.map(|members| members.into_iter().map(String::from).collect()),
+                "{manifest}"
+            );
+        }
+    }
+
+    #[test]
+    fn scan_fn_warns_about_test_functions_without_a_test_attribute() {
+        let (_, warnings) = scan_package(
+            "lint",
+            &[
+                ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+                (
+                    "src/lib.rs",
+                    "pub fn parser_handles_empty_input() {}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn opens_a_file() {
+        assert!(make_temp_file().exists());
+    }
+
+    fn open_file_fn_returns_error_on_failure() {}
+
+    #[should_panic]
+    fn panics_on_bad_input() {}
+
+    fn make_temp_file() -> std::path::PathBuf {
+        std::env::temp_dir()
+    }
+
+    fn with_input_in_a_file(input: &str) {}
+
+    #[rstest::fixture]
+    fn a_test_database() {}
+
+    mod helpers {
+        pub(super) fn assert_files_are_equal() {}
+
+        pub fn assert_dirs_are_equal() {}
+    }
+}
+",
+                ),
+            ],
+        );
+        let warnings: Vec<_> = warnings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            warnings,
+            [
+                "src/lib.rs:10: open_file_fn_returns_error_on_failure is not a test: it has no #[test] attribute, and is never called",
+                "src/lib.rs:13: panics_on_bad_input is not a test: it has no #[test] attribute, and is never called",
+            ]
+        );
+    }
+}
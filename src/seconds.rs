@@ -0,0 +1,38 @@
+//! Serialising durations as a (fractional) number of seconds, which is how
+//! libtest and nextest report them, rather than serde's default format.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use std::time::Duration;
+
+pub(crate) fn serialize<S: Serializer>(duration: &Duration, ser: S) -> Result<S::Ok, S::Error> {
+    ser.serialize_f64(duration.as_secs_f64())
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<Duration, D::Error> {
+    let secs = f64::deserialize(de)?;
+    Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+}
+
+pub(crate) mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    #[allow(clippy::ref_option)] // serde's `with` requires this signature
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        ser: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => ser.serialize_some(&duration.as_secs_f64()),
+            None => ser.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        de: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Option::<f64>::deserialize(de)?
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
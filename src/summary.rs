@@ -0,0 +1,141 @@
+//! Totals for a whole test run.
+
+use colored::Colorize;
+use std::{fmt::Display, time::Duration};
+
+use crate::{Status, TestResult};
+
+/// The number of tests with each status, and the time taken to run them.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    /// Tests that were listed, but not run.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub not_run: usize,
+    #[cfg_attr(feature = "json", serde(with = "crate::seconds"))]
+    pub duration: Duration,
+}
+
+impl Summary {
+    /// Counts the given result towards the totals.
+    pub fn add(&mut self, result: &TestResult) {
+        match result.status {
+            Status::Pass => self.passed += 1,
+            Status::Fail => self.failed += 1,
+            Status::Ignored => self.ignored += 1,
+            Status::NotRun => self.not_run += 1,
+        }
+    }
+
+    /// Returns the total number of tests counted.
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.ignored + self.not_run
+    }
+
+    /// Describes the counts (but not the duration), without colour, such as
+    /// `2 passed, 1 failed, 1 ignored`.
+    pub(crate) fn counts(&self) -> String {
+        format!(
+            "{} passed, {} failed, {} ignored",
+            self.passed, self.failed, self.ignored
+        )
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Tests that were listed (or found by scanning the source) without
+        // being run, some of which may be marked as ignored.
+        if self.not_run > 0 && self.passed == 0 && self.failed == 0 {
+            let listed = self.not_run + self.ignored;
+            let tests = if listed == 1 { "test" } else { "tests" };
+            write!(f, "{listed} {tests} listed")?;
+            if self.ignored > 0 {
+                write!(f, " ({} ignored)", self.ignored)?;
+            }
+            return Ok(());
+        }
+        let passed = format!("{} passed", self.passed);
+        let failed = format!("{} failed", self.failed);
+        let ignored = format!("{} ignored", self.ignored);
+        write!(
+            f,
+            "{}, {}, {} in {:.2}s",
+            if self.passed > 0 {
+                passed.bright_green()
+            } else {
+                passed.normal()
+            },
+            if self.failed > 0 {
+                failed.bright_red()
+            } else {
+                failed.normal()
+            },
+            if self.ignored > 0 {
+                ignored.bright_yellow()
+            } else {
+                ignored.normal()
+            },
+            self.duration.as_secs_f64(),
+        )?;
+        if self.not_run > 0 {
+            write!(f, " ({} not run)", self.not_run)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_test_results;
+
+    #[test]
+    fn summary_counts_each_status_and_shows_elapsed_time() {
+        colored::control::set_override(false);
+        let mut summary = Summary {
+            duration: Duration::from_millis(4200),
+            ..Summary::default()
+        };
+        let results = parse_test_results(
+            "test a ... ok
+test b ... ok
+test c ... FAILED
+test d ... ignored
+",
+        );
+        for result in &results {
+            summary.add(result);
+        }
+        assert_eq!(summary.total(), 4);
+        assert_eq!(
+            summary.to_string(),
+            "2 passed, 1 failed, 1 ignored in 4.20s"
+        );
+    }
+
+    #[test]
+    fn summary_of_listed_tests_just_counts_them() {
+        for (output, want) in [
+            ("test a ... ok\n", "1 test listed"),
+            ("test a ... ok\ntest b ... ok\n", "2 tests listed"),
+            (
+                "test a ... ok\ntest b ... ignored\n",
+                "2 tests listed (1 ignored)",
+            ),
+        ] {
+            let mut summary = Summary::default();
+            for mut result in parse_test_results(output) {
+                if result.status == Status::Pass {
+                    result.status = Status::NotRun;
+                }
+                summary.add(&result);
+            }
+            assert_eq!(summary.to_string(), want, "{output}");
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! The symbols used to show test results.
+
+/// The separator between a test's module and its name, using only ASCII
+/// characters if `ascii` is true.
+pub(crate) fn separator(ascii: bool) -> &'static str {
+    if ascii {
+        "-"
+    } else {
+        "–"
+    }
+}
+
+/// The marker for output that has been cut short, using only ASCII
+/// characters if `ascii` is true.
+pub(crate) fn ellipsis(ascii: bool) -> &'static str {
+    if ascii {
+        "..."
+    } else {
+        "…"
+    }
+}
@@ -0,0 +1,267 @@
+//! Identifying the test binaries (targets) that results come from.
+
+use std::fmt::Display;
+
+/// A test binary run by `cargo test`, such as a library's unit tests or an
+/// integration test file.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json", derive(serde::Serialize, serde::Deserialize))]
+pub struct Target {
+    /// The crate (package) the target belongs to, if known, so that targets
+    /// with the same name in different crates of a workspace can be told
+    /// apart.
+    #[cfg_attr(feature = "json", serde(rename = "crate", default))]
+    pub krate: Option<String>,
+    /// The name of the target: for a library, its crate name; for anything
+    /// else, the name of the binary, integration test, or example.
+    pub name: String,
+    pub kind: TargetKind,
+    /// The hash in the file name of the test binary, such as
+    /// `0123456789abcdef`, if known, so that different binaries with the
+    /// same name are never mistaken for each other, even if their crate
+    /// isn't known.
+    #[cfg_attr(feature = "json", serde(default))]
+    pub hash: Option<String>,
+}
+
+/// The kind of a [`Target`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "json",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+#[non_exhaustive]
+pub enum TargetKind {
+    Lib,
+    Bin,
+    Test,
+    Bench,
+    Example,
+    Doc,
+}
+
+impl Target {
+    /// Parses the line `cargo test` prints before running each test binary,
+    /// such as:
+    ///
+    /// ```text
+    ///      Running unittests src/lib.rs (target/debug/deps/foo-0123456789abcdef)
+    ///      Running tests/cli.rs (target/debug/deps/cli-0123456789abcdef)
+    ///    Doc-tests foo
+    /// ```
+    ///
+    /// If the line announces a test binary, returns `Some(Target)`,
+    /// otherwise returns `None`.
+    ///
+    /// The binary's file name only gives the crate for a library or a
+    /// `src/main.rs` binary, since the source path is relative to the
+    /// package's own directory. For other targets, the crate is left for
+    /// [`Parser`](crate::Parser) to find in cargo's JSON messages, if there
+    /// are any.
+    #[must_use]
+    pub fn from_cargo_line(line: &str) -> Option<Self> {
+        let line = line.trim_start();
+        if let Some(name) = line.strip_prefix("Doc-tests ") {
+            let name = name.trim().to_string();
+            return Some(Target {
+                krate: Some(name.clone()),
+                name,
+                kind: TargetKind::Doc,
+                hash: None,
+            });
+        }
+        let line = line.strip_prefix("Running ")?;
+        let (source, binary) = line.trim_end().strip_suffix(')')?.rsplit_once(" (")?;
+        let (source, unit_tests) = match source.strip_prefix("unittests ") {
+            Some(source) => (source, true),
+            None => (source, false),
+        };
+        let source = source.replace('\\', "/");
+        let kind = if source.starts_with("benches/") {
+            TargetKind::Bench
+        } else if source.starts_with("examples/") {
+            TargetKind::Example
+        } else if !unit_tests {
+            TargetKind::Test
+        } else if source.ends_with("src/main.rs") || source.contains("src/bin/") {
+            TargetKind::Bin
+        } else {
+            TargetKind::Lib
+        };
+        let file = binary.rsplit(['/', '\\']).next()?;
+        let file = file.strip_suffix(".exe").unwrap_or(file);
+        let (name, hash) = match file.rsplit_once('-') {
+            Some((name, hash)) => (name, Some(hash.to_string())),
+            None => (file, None),
+        };
+        let krate =
+            (kind == TargetKind::Lib || source.ends_with("src/main.rs")).then(|| name.to_string());
+        Some(Target {
+            krate,
+            name: name.to_string(),
+            kind,
+            hash,
+        })
+    }
+
+    /// Parses a nextest binary ID, such as `foo` (the library unit tests of
+    /// crate `foo`), `foo::bin/bar`, or `foo::cli` (an integration test).
+    #[must_use]
+    pub fn from_nextest_binary_id(binary_id: &str) -> Self {
+        let Some((krate, binary)) = binary_id.split_once("::") else {
+            return Target {
+                krate: Some(binary_id.to_string()),
+                name: binary_id.to_string(),
+                kind: TargetKind::Lib,
+                hash: None,
+            };
+        };
+        let (kind, name) = match binary.split_once('/') {
+            Some(("bin", name)) => (TargetKind::Bin, name),
+            Some(("bench", name)) => (TargetKind::Bench, name),
+            Some(("example", name)) => (TargetKind::Example, name),
+            Some(("test", name)) => (TargetKind::Test, name),
+            _ => (TargetKind::Test, binary),
+        };
+        let name = if name.is_empty() { krate } else { name };
+        Target {
+            krate: Some(krate.to_string()),
+            name: name.to_string(),
+            kind,
+            hash: None,
+        }
+    }
+}
+
+impl Target {
+    /// Returns the target's name, with its crate too if that's different,
+    /// such as `foo::cli`.
+    pub(crate) fn display_name(&self) -> String {
+        match &self.krate {
+            Some(krate) if *krate != self.name => format!("{krate}::{}", self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+impl Display for Target {
+    /// Shows the target's name and kind, such as `cli (test)`, with its
+    /// crate too if that's different, such as `foo::cli (test)`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.display_name(), self.kind)
+    }
+}
+
+impl Display for TargetKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            TargetKind::Lib => "lib",
+            TargetKind::Bin => "bin",
+            TargetKind::Test => "test",
+            TargetKind::Bench => "bench",
+            TargetKind::Example => "example",
+            TargetKind::Doc => "doctests",
+        };
+        write!(f, "{kind}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_cargo_line_fn_returns_expected_result() {
+        struct Case {
+            line: &'static str,
+            want: Option<(
+                Option<&'static str>,
+                &'static str,
+                TargetKind,
+                Option<&'static str>,
+            )>,
+        }
+        let cases = Vec::from([
+            Case {
+                line: "   Compiling foo v0.1.0 (/home/user/foo)",
+                want: None,
+            },
+            Case {
+                line: "     Running unittests src/lib.rs (target/debug/deps/foo_bar-760c8cd3fed676c2)",
+                want: Some((Some("foo_bar"), "foo_bar", TargetKind::Lib, Some("760c8cd3fed676c2"))),
+            },
+            Case {
+                line: "     Running unittests src/main.rs (target/debug/deps/foo-0d4b7a93e314c299)",
+                want: Some((Some("foo"), "foo", TargetKind::Bin, Some("0d4b7a93e314c299"))),
+            },
+            Case {
+                line: "     Running unittests src/bin/tool.rs (target/debug/deps/tool-0d4b7a93e314c299)",
+                want: Some((None, "tool", TargetKind::Bin, Some("0d4b7a93e314c299"))),
+            },
+            Case {
+                line: "     Running tests/cli.rs (target/debug/deps/cli-19da1b0f0ec8cbd3)",
+                want: Some((None, "cli", TargetKind::Test, Some("19da1b0f0ec8cbd3"))),
+            },
+            Case {
+                line: "     Running tests/cli.rs (target/debug/deps/cli-b649f6ea8b966137)",
+                want: Some((None, "cli", TargetKind::Test, Some("b649f6ea8b966137"))),
+            },
+            Case {
+                line: r"     Running tests\cli.rs (target\debug\deps\cli-984ba73748ad56ba.exe)",
+                want: Some((None, "cli", TargetKind::Test, Some("984ba73748ad56ba"))),
+            },
+            Case {
+                line: "     Running benches/speed.rs (target/release/deps/speed-984ba73748ad56ba)",
+                want: Some((None, "speed", TargetKind::Bench, Some("984ba73748ad56ba"))),
+            },
+            Case {
+                line: "   Doc-tests foo_bar",
+                want: Some((Some("foo_bar"), "foo_bar", TargetKind::Doc, None)),
+            },
+        ]);
+        for case in cases {
+            let want = case.want.map(|(krate, name, kind, hash)| Target {
+                krate: krate.map(Into::into),
+                name: name.into(),
+                kind,
+                hash: hash.map(Into::into),
+            });
+            assert_eq!(want, Target::from_cargo_line(case.line), "{}", case.line);
+        }
+    }
+
+    #[test]
+    fn from_nextest_binary_id_fn_returns_expected_result() {
+        let cases = [
+            ("foo", "foo", "foo", TargetKind::Lib),
+            ("foo::bin/tool", "foo", "tool", TargetKind::Bin),
+            ("foo::cli", "foo", "cli", TargetKind::Test),
+            ("bar::cli", "bar", "cli", TargetKind::Test),
+            ("foo::bench/speed", "foo", "speed", TargetKind::Bench),
+            ("foo::example/demo", "foo", "demo", TargetKind::Example),
+        ];
+        for (binary_id, krate, name, kind) in cases {
+            let want = Target {
+                krate: Some(krate.into()),
+                name: name.into(),
+                kind,
+                hash: None,
+            };
+            assert_eq!(want, Target::from_nextest_binary_id(binary_id));
+        }
+    }
+
+    #[test]
+    fn display_shows_the_crate_when_it_differs_from_the_target_name() {
+        let target = |krate: Option<&str>, name: &str| Target {
+            krate: krate.map(Into::into),
+            name: name.into(),
+            kind: TargetKind::Test,
+            hash: None,
+        };
+        assert_eq!(target(Some("foo"), "cli").to_string(), "foo::cli (test)");
+        assert_eq!(target(Some("cli"), "cli").to_string(), "cli (test)");
+        assert_eq!(target(None, "cli").to_string(), "cli (test)");
+    }
+}
@@ -0,0 +1,8 @@
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn does_not_compile() {
+        let answer: u32 = "forty-two";
+        assert_eq!(answer, 42);
+    }
+}
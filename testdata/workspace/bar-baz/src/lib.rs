@@ -0,0 +1 @@
+//! Part of a workspace whose members both have a `tests/cli.rs`.
@@ -0,0 +1,11 @@
+//! Part of a workspace whose members both have a `tests/cli.rs`.
+
+/// Returns the answer.
+///
+/// ```
+/// assert_eq!(foo::answer(), 42);
+/// ```
+#[must_use]
+pub fn answer() -> u32 {
+    42
+}
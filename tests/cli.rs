@@ -11,5 +11,371 @@ fn passing_include_ignored_flag_runs_ignored_tests() {
         .arg("--include-ignored")
         .assert()
         .success()
-        .stdout(predicate::eq("✔ ignored test\n"));
+        .stdout(predicate::str::starts_with(
+            "testproj (lib)\n✔ ignored test\n\n1 passed, 0 failed, 0 ignored in ",
+        ));
+}
+
+#[test]
+fn list_flag_shows_tests_without_running_them() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout("testproj (lib)\n• ignored test\n\n1 test listed\n");
+}
+
+#[test]
+#[cfg(feature = "scan")]
+fn scan_flag_finds_tests_in_the_source_without_building_them() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/brokenproj")
+        .arg("testdox")
+        .arg("--scan")
+        .assert()
+        .success()
+        .stdout("brokenproj (lib)\n• does not compile (src/lib.rs:4)\n\n1 test listed\n");
+}
+
+#[test]
+fn same_named_tests_in_different_workspace_members_are_told_apart() {
+    // The second run has nothing to rebuild.
+    for _ in 0..2 {
+        Command::cargo_bin("cargo-testdox")
+            .unwrap()
+            .current_dir("testdata/workspace")
+            .arg("testdox")
+            .arg("--test")
+            .arg("cli")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "bar_baz::cli (test)\n✔ cli works\n",
+            ))
+            .stdout(predicate::str::contains("foo::cli (test)\n✔ cli works\n"))
+            .stdout(predicate::str::contains(
+                "\n2 passed, 0 failed, 0 ignored in ",
+            ));
+    }
+}
+
+#[test]
+fn no_doctests_flag_hides_the_results_of_doctests() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/workspace")
+        .args(["testdox", "-p", "foo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "foo (doctests)\n✔ answer – example (foo/src/lib.rs:5)\n",
+        ))
+        .stdout(predicate::str::contains(
+            "\n2 passed, 0 failed, 0 ignored in ",
+        ));
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/workspace")
+        .args(["testdox", "--no-doctests", "-p", "foo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("foo::cli (test)\n✔ cli works\n"))
+        .stdout(predicate::str::contains("doctests").not())
+        .stdout(predicate::str::contains(
+            "\n1 passed, 0 failed, 0 ignored in ",
+        ));
+}
+
+#[test]
+#[cfg(feature = "scan")]
+fn scan_flag_finds_the_tests_of_each_workspace_member() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/workspace")
+        .arg("testdox")
+        .arg("--scan")
+        .assert()
+        .success()
+        .stdout(
+            "foo::cli (test)
+• cli works (foo/tests/cli.rs:2)
+bar_baz::cli (test)
+• cli works (bar-baz/tests/cli.rs:2)
+
+2 tests listed
+",
+        );
+}
+
+#[test]
+fn libtest_json_flag_reports_the_same_results() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--libtest-json")
+        .arg("--")
+        .arg("--include-ignored")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "testproj (lib)\n✔ ignored test\n\n1 passed, 0 failed, 0 ignored in ",
+        ));
+}
+
+#[test]
+fn unknown_runner_is_reported_as_an_error() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--runner")
+        .arg("bogus")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown test runner"));
+}
+
+#[test]
+fn help_flag_describes_options() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .arg("testdox")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--format <FORMAT>"))
+        .stdout(predicate::str::contains("--runner <RUNNER>"));
+}
+
+#[test]
+fn help_flag_after_merge_describes_the_merge_command() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .arg("testdox")
+        .arg("merge")
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "Merge the results of several test runs",
+        ))
+        .stdout(predicate::str::contains("<FILE>..."));
+}
+
+#[test]
+fn testdox_options_are_not_passed_to_cargo_test() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--format")
+        .arg("grouped")
+        .arg("--lib")
+        .arg("--")
+        .arg("--include-ignored")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "testproj (lib)\n  ✔ ignored test\n",
+        ));
+}
+
+#[test]
+fn testdox_options_are_recognised_after_cargo_test_options() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--lib")
+        .arg("--format")
+        .arg("grouped")
+        .arg("--ascii")
+        .arg("--")
+        .arg("--include-ignored")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "testproj (lib)\n  PASS ignored test\n\n1 passed, 0 failed, 0 ignored in ",
+        ));
+}
+
+#[test]
+fn output_flag_writes_html_report_to_given_path() {
+    let path = std::env::temp_dir().join(format!("testdox-{}.html", std::process::id()));
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--format")
+        .arg("html")
+        .arg("--output")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    let html = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"), "{html}");
+    assert!(html.contains("ignored test"), "{html}");
+}
+
+#[test]
+fn build_errors_are_shown_and_reported_as_a_failure() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/brokenproj")
+        .arg("testdox")
+        .assert()
+        .code(3)
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("error[E0308]: mismatched types"))
+        .stderr(predicate::str::contains("build failed"));
+}
+
+#[test]
+fn arguments_cargo_rejects_are_reported_as_an_error_not_a_build_failure() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--no-such-flag")
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(
+            "unexpected argument '--no-such-flag'",
+        ))
+        .stderr(predicate::str::contains("test command failed"))
+        .stderr(predicate::str::contains("build failed").not());
+}
+
+#[test]
+fn running_no_tests_is_reported_as_a_failure() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--lib")
+        .arg("no_such_test")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("no tests ran"));
+}
+
+#[test]
+fn ascii_flag_shows_plain_markers_instead_of_symbols() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--ascii")
+        .arg("--")
+        .arg("--include-ignored")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "testproj (lib)\nPASS ignored test\n",
+        ));
+}
+
+#[test]
+fn color_flag_controls_whether_output_is_coloured() {
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .arg("testdox")
+        .arg("--color")
+        .arg("always")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}["));
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir("testdata/testproj")
+        .env("CLICOLOR_FORCE", "1")
+        .arg("testdox")
+        .arg("--color")
+        .arg("never")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}[").not());
+}
+
+#[test]
+fn input_flag_reads_test_output_from_a_saved_log() {
+    let log = "2024-05-01T12:34:56.1234567Z \x1b[1m\x1b[92m     Running\x1b[0m unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)\r
+2024-05-01T12:34:56.1234567Z test tests::it_works ... \x1b[32mok\x1b[0m\r
+2024-05-01T12:34:56.1234567Z test tests::it_fails ... \x1b[31mFAILED\x1b[0m\r
+";
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .arg("testdox")
+        .arg("--input")
+        .arg("-")
+        .write_stdin(log)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with(
+            "demo (lib)\n✔ it works\nx it fails\n\n1 passed, 1 failed, 0 ignored in ",
+        ));
+}
+
+#[test]
+fn strict_flag_fails_when_the_counts_libtest_reports_do_not_match() {
+    let log = "     Running tests/cli.rs (target/debug/deps/cli-0123456789abcdef)
+test tests::it_works ... ok
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+    let warning = "warning: cli (test): libtest reported 2 passed, 0 failed, 0 ignored, \
+        but testdox found 1 passed, 0 failed, 0 ignored";
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .args(["testdox", "--input", "-"])
+        .write_stdin(log)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(warning));
+    Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .args(["testdox", "--strict", "--input", "-"])
+        .write_stdin(log)
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains(warning))
+        .stderr(predicate::str::contains(
+            "Error: 1 warning about the test output (with --strict)",
+        ));
+}
+
+#[test]
+fn merge_command_combines_results_of_several_runs() {
+    let dir = std::env::temp_dir().join(format!("testdox-merge-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let running = "     Running unittests src/lib.rs (target/debug/deps/demo-0123456789abcdef)\n";
+    std::fs::write(
+        dir.join("shard1.log"),
+        format!("{running}test tests::it_works ... ok\ntest tests::is_flaky ... FAILED\n"),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("shard2.log"),
+        format!("{running}test tests::is_flaky ... ok\ntest tests::also_works ... ok\n"),
+    )
+    .unwrap();
+    let assert = Command::cargo_bin("cargo-testdox")
+        .unwrap()
+        .current_dir(&dir)
+        .arg("testdox")
+        .arg("merge")
+        .arg("shard1.log")
+        .arg("shard2.log")
+        .assert();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert.code(1).stdout(predicate::str::starts_with(
+        "demo (lib)\n✔ it works\nx is flaky (flaky)\n✔ also works\n\n2 passed, 1 failed, 0 ignored in ",
+    ));
 }